@@ -0,0 +1,27 @@
+use std::fmt;
+
+use culpa::throw;
+
+#[derive(Debug)]
+struct ConfigError(std::io::Error);
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("could not load the config")
+    }
+}
+
+impl std::error::Error for ConfigError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.0)
+    }
+}
+
+// Prints the error and its cause, and exits with code 2:
+//
+// Error: could not load the config
+// Caused by: entity not found
+#[culpa::main(ConfigError, exit_code = 2)]
+fn main() {
+    throw!(ConfigError(std::io::ErrorKind::NotFound.into()));
+}
@@ -0,0 +1,578 @@
+// The Args type parses the arguments to the `#[throws]` macro.
+//
+// Besides the error and wrapper types, they may end with a `debug` argument
+// to show the generated code, see the debug module, and a `crate = path`
+// argument giving the path to culpa for the generated code to use. They are
+// also the only arguments of `#[try_fn]`, which TryFnArgs parses.
+//
+// It is also responsible for transforming the return type by injecting
+// the return type and the error type into the wrapper type. They are added
+// to the generic arguments of its innermost path, unless the wrapper marks
+// where they go with `@ok` and `@err` placeholders, as in
+// `E as MyResult<'a, @ok, Ctx, @err>`.
+
+use proc_macro2::{Group, Ident, Spacing, Span, TokenStream, TokenTree};
+use syn::fold::Fold;
+use syn::parse::{Error, Parse, ParseStream, Result};
+use syn::punctuated::Punctuated;
+use syn::{GenericArgument, Path, PathArguments, PathSegment, ReturnType, Token, Type, TypePath};
+
+const WRAPPER_MUST_BE_PATH: &str = "wrapper type must be a normal path type";
+
+mod kw {
+    syn::custom_keyword!(debug);
+}
+
+/// The arguments of `#[throws]`: an error type, a wrapper type, `debug` and `crate = path`.
+///
+/// They are parsed the same way as by `#[throws]`, where no arguments at all mean the default error
+/// type, a type named `Error`.
+#[derive(Clone)]
+pub struct Args {
+    error: Option<Type>,
+    // A list of error types like `A | B`, which a generated enum of all of
+    // them must replace as the error type before the arguments are used.
+    errors: Option<Punctuated<Type, Token![|]>>,
+    wrapper: TypePath,
+    // Whether the wrapper has `@ok` and `@err` placeholders, replaced by
+    // OK_PLACEHOLDER and ERR_PLACEHOLDER when parsed.
+    placeholders: bool,
+    debug: Option<Span>,
+    krate: Option<Path>,
+}
+
+const OK_PLACEHOLDER: &str = "__culpa_ok";
+const ERR_PLACEHOLDER: &str = "__culpa_err";
+
+impl Args {
+    /// Transforms the return type of a function, injecting it and the error type into the
+    /// wrapper type.
+    pub fn ret(&self, ret: ReturnType) -> ReturnType {
+        let (arrow, ret) = match ret {
+            ReturnType::Default => (arrow(), unit()),
+            ReturnType::Type(arrow, ty) => (arrow, *ty),
+        };
+        ReturnType::Type(arrow, Box::new(self.inject_to_wrapper(ret)))
+    }
+
+    /// The error type, which is none with `as Wrapper` on its own.
+    pub fn error(&self) -> Option<&Type> {
+        self.error.as_ref()
+    }
+
+    pub(crate) fn errors(&self) -> Option<&Punctuated<Type, Token![|]>> {
+        self.errors.as_ref()
+    }
+
+    /// The span of the `debug` argument, if it was given.
+    pub fn debug(&self) -> Option<Span> {
+        self.debug
+    }
+
+    /// The path to culpa given with `crate = path`, if any.
+    pub fn krate(&self) -> Option<&Path> {
+        self.krate.as_ref()
+    }
+
+    // Whether the wrapper is `ResultOption`, for functions returning
+    // `Result<Option<T>, E>` whose values are wrapped in `Some`.
+    pub(crate) fn optional(&self) -> bool {
+        self.wrapper.path.is_ident("ResultOption")
+    }
+
+    pub(crate) fn with_error(self, error: Type) -> Args {
+        Args {
+            error: Some(error),
+            errors: None,
+            ..self
+        }
+    }
+
+    fn inject_to_wrapper(&self, ret: Type) -> Type {
+        if self.optional() {
+            let error = &self.error;
+            return syn::parse_quote! {
+                ::core::result::Result<::core::option::Option<#ret>, #error>
+            };
+        }
+        if self.placeholders {
+            let mut placeholders = Placeholders {
+                ok: ret,
+                error: self.error.clone(),
+            };
+            return placeholders.fold_type(Type::Path(self.wrapper.clone()));
+        }
+        let mut wrapper = self.wrapper.clone();
+        let segment = innermost_segment(&mut wrapper.path);
+        let types = match self.error.clone() {
+            // `ControlFlow` takes the type it breaks with, which is what is
+            // thrown, before the type it continues with
+            Some(error) if segment.ident == "ControlFlow" => vec![error, ret],
+            Some(error) => vec![ret, error],
+            None => vec![ret],
+        };
+        let types = types.into_iter().map(GenericArgument::Type);
+
+        match &mut segment.arguments {
+            args @ PathArguments::None => {
+                *args = PathArguments::AngleBracketed(syn::AngleBracketedGenericArguments {
+                    colon2_token: None,
+                    lt_token: Token![<](Span::call_site()),
+                    args: types.collect(),
+                    gt_token: Token![>](Span::call_site()),
+                });
+            }
+            PathArguments::AngleBracketed(args) => args.args.extend(types),
+            PathArguments::Parenthesized(_) => unreachable!(),
+        }
+
+        Type::Path(wrapper)
+    }
+}
+
+// The input to the function-like macros: the usual arguments followed by a
+// `=>` and the item to transform. The arguments may be omitted entirely.
+pub struct ArgsAnd<T> {
+    pub args: Args,
+    pub item: T,
+}
+
+impl<T: Parse> Parse for ArgsAnd<T> {
+    fn parse(input: ParseStream) -> Result<ArgsAnd<T>> {
+        let args = match has_arrow(input) {
+            true => {
+                let args = input.parse()?;
+                input.parse::<Token![=>]>()?;
+                args
+            }
+            false => Args::default(),
+        };
+        let item = input.parse()?;
+        Ok(ArgsAnd { args, item })
+    }
+}
+
+// The input to `try_block!`, which also takes a bare `Option` to mean
+// `as Option`, since a block has no ok type written in front of the `as`. It
+// can be followed by the `debug` and `crate = path` options.
+pub struct TryBlock {
+    pub args: Args,
+    pub block: syn::Block,
+}
+
+impl Parse for TryBlock {
+    fn parse(input: ParseStream) -> Result<TryBlock> {
+        let fork = input.fork();
+        let option = matches!(fork.parse::<syn::Ident>(), Ok(ident) if ident == "Option");
+        if option && (fork.peek(Token![=>]) || fork.peek(Token![,])) {
+            input.parse::<syn::Ident>()?;
+            let (debug, krate) = options(input, false)?;
+            if !input.peek(Token![=>]) {
+                return Err(unexpected(input));
+            }
+            input.parse::<Token![=>]>()?;
+            let args = Args {
+                error: None,
+                errors: None,
+                wrapper: syn::parse_quote!(::core::option::Option),
+                placeholders: false,
+                debug,
+                krate,
+            };
+            return Ok(TryBlock {
+                args,
+                block: input.parse()?,
+            });
+        }
+        let ArgsAnd { args, item } = input.parse()?;
+        Ok(TryBlock { args, block: item })
+    }
+}
+
+// Whether there is a `=>` outside of any delimiters, which can only come
+// after arguments.
+pub(crate) fn has_arrow(input: ParseStream) -> bool {
+    let fork = input.fork();
+    while !fork.is_empty() {
+        if fork.peek(Token![=>]) {
+            return true;
+        }
+        let _ = fork.parse::<proc_macro2::TokenTree>();
+    }
+    false
+}
+
+impl Default for Args {
+    fn default() -> Args {
+        Args {
+            error: Some(default_error()),
+            errors: None,
+            wrapper: result(),
+            placeholders: false,
+            debug: None,
+            krate: None,
+        }
+    }
+}
+
+impl Parse for Args {
+    fn parse(input: ParseStream) -> Result<Args> {
+        if input.is_empty() || peek_option(input) {
+            let (debug, krate) = options(input, true)?;
+            if !input.is_empty() && !input.peek(Token![=>]) {
+                return Err(unexpected(input));
+            }
+            return Ok(Args {
+                debug,
+                krate,
+                ..Args::default()
+            });
+        }
+
+        let error = match input.peek(Token![as]) {
+            true => None,
+            false => {
+                let error = input.parse()?;
+                Some(match error {
+                    Type::Infer(_) => default_error(),
+                    _ => error,
+                })
+            }
+        };
+
+        let errors = match (&error, input.peek(Token![|])) {
+            (Some(error), true) => {
+                let mut errors = Punctuated::new();
+                errors.push_value(error.clone());
+                while input.peek(Token![|]) {
+                    errors.push_punct(input.parse()?);
+                    errors.push_value(input.parse()?);
+                }
+                Some(errors)
+            }
+            _ => None,
+        };
+
+        let placeholders = input.peek(Token![as]) && has_placeholders(input);
+        let wrapper = match input.parse::<Token![as]>().is_ok() {
+            true if placeholders => placeholder_wrapper(input, &error)?,
+            true => wrapper(shorthand(input.parse()?))?,
+            false => result(),
+        };
+
+        let control_flow =
+            !placeholders && wrapper.path.segments.last().unwrap().ident == "ControlFlow";
+        if control_flow && error.is_none() {
+            return Err(Error::new_spanned(
+                wrapper,
+                "`ControlFlow` needs the type to break with, as in `B as ControlFlow`",
+            ));
+        }
+        if wrapper.path.is_ident("ResultOption") && error.is_none() {
+            return Err(Error::new_spanned(
+                wrapper,
+                "`ResultOption` needs the error type, as in `E as ResultOption`",
+            ));
+        }
+
+        let (debug, krate) = options(input, false)?;
+
+        if !input.is_empty() && !input.peek(Token![=>]) {
+            return Err(unexpected(input));
+        }
+
+        Ok(Args {
+            error,
+            errors,
+            wrapper,
+            placeholders,
+            debug,
+            krate,
+        })
+    }
+}
+
+#[derive(Default)]
+pub struct TryFnArgs {
+    debug: Option<Span>,
+    krate: Option<Path>,
+}
+
+impl TryFnArgs {
+    pub fn debug(&self) -> Option<Span> {
+        self.debug
+    }
+
+    pub fn krate(&self) -> Option<&Path> {
+        self.krate.as_ref()
+    }
+}
+
+impl Parse for TryFnArgs {
+    fn parse(input: ParseStream) -> Result<TryFnArgs> {
+        let args: proc_macro2::TokenStream = input.fork().parse()?;
+        let (debug, krate) = options(input, true)?;
+        if !input.is_empty() {
+            return Err(Error::new_spanned(
+                args,
+                "#[try_fn] does not take arguments other than `debug` and `crate = path`",
+            ));
+        }
+        Ok(TryFnArgs { debug, krate })
+    }
+}
+
+// Whether the next argument is `debug` or `crate = path`.
+fn peek_option(input: ParseStream) -> bool {
+    input.peek(kw::debug) || (input.peek(Token![crate]) && input.peek2(Token![=]))
+}
+
+// Parses the `debug` and `crate = path` arguments, which follow a comma
+// unless they are the first arguments.
+fn options(input: ParseStream, first: bool) -> Result<(Option<Span>, Option<Path>)> {
+    let mut debug = None;
+    let mut krate = None;
+    let mut comma = !first;
+    loop {
+        if comma {
+            let fork = input.fork();
+            if fork.parse::<Token![,]>().is_err() || !peek_option(&fork) {
+                return Ok((debug, krate));
+            }
+            input.parse::<Token![,]>()?;
+        } else if !peek_option(input) {
+            return Ok((debug, krate));
+        }
+        comma = true;
+        if input.peek(kw::debug) {
+            debug = Some(input.parse::<kw::debug>()?.span);
+        } else {
+            input.parse::<Token![crate]>()?;
+            input.parse::<Token![=]>()?;
+            krate = Some(Path::parse_mod_style(input)?);
+        }
+    }
+}
+
+// Builds an error spanning all the remaining arguments.
+fn unexpected(input: ParseStream) -> Error {
+    let _ = input.parse::<Token![,]>();
+    let mut rest = proc_macro2::TokenStream::new();
+    while !input.is_empty() && !input.peek(Token![=>]) {
+        match input.parse::<proc_macro2::TokenTree>() {
+            Ok(tt) => rest.extend(Some(tt)),
+            Err(error) => return error,
+        }
+    }
+    Error::new_spanned(
+        rest,
+        "unexpected argument, expected `Error`, `as Wrapper` or `Error as Wrapper`",
+    )
+}
+
+// Expands the shorthand wrapper types:
+// - `Poll` is `Poll<Result>`, for implementing futures.
+// - `PollStream` is `Poll<Option<Result>>`, for implementing streams.
+fn shorthand(ty: Type) -> Type {
+    if let Type::Path(TypePath { qself: None, path }) = &ty {
+        let last = path.segments.last().unwrap();
+        if last.arguments.is_none() {
+            if last.ident == "Poll" {
+                let mut path = path.clone();
+                path.segments.last_mut().unwrap().arguments =
+                    PathArguments::AngleBracketed(syn::parse_quote!(<::core::result::Result>));
+                return Type::Path(TypePath { qself: None, path });
+            } else if path.is_ident("PollStream") {
+                return syn::parse_quote! {
+                    ::core::task::Poll<::core::option::Option<::core::result::Result>>
+                };
+            }
+        }
+    }
+    ty
+}
+
+// Whether the wrapper type after `as` has `@` placeholders in it.
+fn has_placeholders(input: ParseStream) -> bool {
+    fn has_at(tokens: TokenStream) -> bool {
+        tokens.into_iter().any(|tt| match tt {
+            TokenTree::Punct(punct) => punct.as_char() == '@',
+            TokenTree::Group(group) => has_at(group.stream()),
+            _ => false,
+        })
+    }
+    let fork = input.fork();
+    let _ = fork.parse::<Token![as]>();
+    wrapper_tokens(&fork).is_ok_and(has_at)
+}
+
+// Parses a wrapper type with `@ok` and `@err` placeholders, which must match
+// whether there is an error type.
+fn placeholder_wrapper(input: ParseStream, error: &Option<Type>) -> Result<TypePath> {
+    let tokens = wrapper_tokens(input)?;
+    let mut found = Vec::new();
+    let wrapper = match syn::parse2(replace_placeholders(tokens.clone(), &mut found)?)? {
+        Type::Path(wrapper) if wrapper.qself.is_none() => wrapper,
+        ty => return Err(Error::new_spanned(ty, WRAPPER_MUST_BE_PATH)),
+    };
+    if !found.iter().any(|name| name == "ok") {
+        return Err(Error::new_spanned(
+            tokens,
+            "the wrapper type needs an `@ok` placeholder for the ok type",
+        ));
+    }
+    match (found.iter().find(|name| *name == "err"), error) {
+        (Some(err), None) => Err(Error::new(
+            err.span(),
+            "`@err` needs an error type, as in `E as Wrapper<@ok, @err>`",
+        )),
+        (None, Some(_)) => Err(Error::new_spanned(
+            tokens,
+            "the wrapper type needs an `@err` placeholder for the error type",
+        )),
+        _ => Ok(wrapper),
+    }
+}
+
+// The tokens of a wrapper type, up to the `,` or `=>` after it. Angle brackets
+// are not delimiters, so they are counted to skip the commas between generic
+// arguments, except for the `>` of a `->`.
+fn wrapper_tokens(input: ParseStream) -> Result<TokenStream> {
+    let mut tokens = TokenStream::new();
+    let mut depth = 0usize;
+    let mut arrow = false;
+    while !input.is_empty() && (depth > 0 || !(input.peek(Token![,]) || input.peek(Token![=>]))) {
+        let tt = input.parse::<TokenTree>()?;
+        if let TokenTree::Punct(punct) = &tt {
+            match punct.as_char() {
+                '<' => depth += 1,
+                '>' if !arrow => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+            arrow = punct.as_char() == '-' && punct.spacing() == Spacing::Joint;
+        } else {
+            arrow = false;
+        }
+        tokens.extend(Some(tt));
+    }
+    Ok(tokens)
+}
+
+// Replaces the `@ok` and `@err` placeholders with identifiers, so that the
+// wrapper can be parsed as a type, collecting the names of those found.
+fn replace_placeholders(tokens: TokenStream, found: &mut Vec<Ident>) -> Result<TokenStream> {
+    let mut output = TokenStream::new();
+    let mut tokens = tokens.into_iter();
+    while let Some(tt) = tokens.next() {
+        let tt = match tt {
+            TokenTree::Punct(punct) if punct.as_char() == '@' => {
+                let name = match tokens.next() {
+                    Some(TokenTree::Ident(name)) if name == "ok" || name == "err" => name,
+                    Some(tt) => return Err(Error::new(tt.span(), "expected `ok` or `err`")),
+                    None => return Err(Error::new(punct.span(), "expected `@ok` or `@err`")),
+                };
+                let placeholder = match name == "ok" {
+                    true => OK_PLACEHOLDER,
+                    false => ERR_PLACEHOLDER,
+                };
+                found.push(name.clone());
+                TokenTree::Ident(Ident::new(placeholder, name.span()))
+            }
+            TokenTree::Group(group) => {
+                let stream = replace_placeholders(group.stream(), found)?;
+                let mut replaced = Group::new(group.delimiter(), stream);
+                replaced.set_span(group.span());
+                TokenTree::Group(replaced)
+            }
+            tt => tt,
+        };
+        output.extend(Some(tt));
+    }
+    Ok(output)
+}
+
+// Replaces the placeholders in a wrapper type with the ok and error types.
+struct Placeholders {
+    ok: Type,
+    error: Option<Type>,
+}
+
+impl Fold for Placeholders {
+    fn fold_type(&mut self, i: Type) -> Type {
+        match i {
+            Type::Path(ty) if ty.qself.is_none() && ty.path.is_ident(OK_PLACEHOLDER) => {
+                self.ok.clone()
+            }
+            Type::Path(ty) if ty.qself.is_none() && ty.path.is_ident(ERR_PLACEHOLDER) => {
+                // An error type is required for `@err` when parsing
+                self.error.clone().unwrap()
+            }
+            i => syn::fold::fold_type(self, i),
+        }
+    }
+}
+
+fn wrapper(ty: Type) -> Result<TypePath> {
+    match ty {
+        Type::Path(wrapper) if wrapper.qself.is_none() => {
+            check_wrapper(&wrapper.path)?;
+            Ok(wrapper)
+        }
+        ty => Err(Error::new_spanned(ty, WRAPPER_MUST_BE_PATH)),
+    }
+}
+
+// Checks that innermost_segment will be able to find where to inject
+// the ok and error types.
+fn check_wrapper(path: &Path) -> Result<()> {
+    match &path.segments.last().unwrap().arguments {
+        PathArguments::None => Ok(()),
+        PathArguments::AngleBracketed(args) => match args.args.last() {
+            Some(GenericArgument::Type(Type::Path(inner))) if inner.qself.is_none() => {
+                check_wrapper(&inner.path)
+            }
+            // Cases like `#[throw(_ as MyTryType<'a>)]` need placeholders instead
+            Some(arg) => Err(Error::new_spanned(
+                arg,
+                "unsupported wrapper type, the last generic argument must be a normal path type, or the ok type must be marked with `@ok`",
+            )),
+            None => Err(Error::new_spanned(
+                args,
+                "unsupported wrapper type, the generic arguments must not be empty",
+            )),
+        },
+        PathArguments::Parenthesized(args) => Err(Error::new_spanned(args, WRAPPER_MUST_BE_PATH)),
+    }
+}
+
+// The last segment of the innermost path in a wrapper type, which the ok and
+// error types are injected into.
+fn innermost_segment(path: &mut Path) -> &mut PathSegment {
+    let segment = path.segments.last_mut().unwrap();
+    if segment.arguments.is_none() {
+        return segment;
+    }
+    match &mut segment.arguments {
+        PathArguments::AngleBracketed(args) => match args.args.last_mut() {
+            Some(GenericArgument::Type(Type::Path(inner))) => innermost_segment(&mut inner.path),
+            _ => unreachable!("checked by check_wrapper"),
+        },
+        _ => unreachable!("checked by check_wrapper"),
+    }
+}
+
+fn arrow() -> syn::token::RArrow {
+    Token![->](Span::call_site())
+}
+
+fn unit() -> Type {
+    syn::parse_str("()").unwrap()
+}
+
+fn result() -> TypePath {
+    syn::parse_str("::core::result::Result").unwrap()
+}
+
+fn default_error() -> Type {
+    syn::parse_str("Error").unwrap()
+}
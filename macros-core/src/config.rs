@@ -0,0 +1,350 @@
+// This module implements the `config` attribute.
+//
+// It sets the default error type and the path to culpa for the macros in an
+// inline module, so that the default error type does not have to be a type
+// named `Error` in scope. It adds them to the arguments of the `#[throws]`,
+// `#[try_fn]` and `#[culpa::main]` attributes and the `throws_closure!`,
+// `throws_async!` and `try_block!` invocations in the module that do not give
+// them, before those macros are expanded. The macros are recognized when their
+// paths name culpa's own items under `culpa` or under the path to culpa, or
+// when they are named alone after being imported from culpa by a `use` item of
+// the module or of a block around them. They are not found inside the
+// invocations of other macros.
+//
+// A module inside with its own `#[culpa::config]` is left for it to configure.
+
+use std::cell::OnceCell;
+
+use proc_macro2::{Spacing, TokenStream, TokenTree};
+use quote::{quote, ToTokens};
+use syn::parse::{Parse, ParseStream};
+use syn::visit_mut::VisitMut;
+use syn::{Error, Ident, Path, Result, Token, Type, UseTree};
+
+pub struct Config {
+    error: Option<Type>,
+    krate: Option<Path>,
+    culpa: OnceCell<Path>,
+    imports: Vec<Imports>,
+}
+
+// The names imported by the `use` items of a module or block, which is where
+// a macro named without a path comes from.
+#[derive(Default)]
+struct Imports {
+    module: bool,
+    // Imported from culpa, under a name and maybe renamed
+    names: Vec<(Ident, Ident)>,
+    glob: bool,
+    // Imported from elsewhere, hiding culpa's items of the same names
+    others: Vec<Ident>,
+}
+
+mod kw {
+    syn::custom_keyword!(error);
+}
+
+impl Parse for Config {
+    fn parse(input: ParseStream) -> Result<Config> {
+        let mut config = Config {
+            error: None,
+            krate: None,
+            culpa: OnceCell::new(),
+            imports: Vec::new(),
+        };
+        loop {
+            if input.peek(kw::error) && config.error.is_none() {
+                input.parse::<kw::error>()?;
+                input.parse::<Token![=]>()?;
+                config.error = Some(input.parse()?);
+            } else if input.peek(Token![crate]) && config.krate.is_none() {
+                input.parse::<Token![crate]>()?;
+                input.parse::<Token![=]>()?;
+                config.krate = Some(Path::parse_mod_style(input)?);
+            } else {
+                return Err(input.error("expected `error = Type` or `crate = path`"));
+            }
+            if input.is_empty() {
+                return Ok(config);
+            }
+            input.parse::<Token![,]>()?;
+        }
+    }
+}
+
+const INLINE_MODULES_ONLY: &str = "#[culpa::config] can only be applied to inline modules";
+
+pub fn config(config: Config, input: TokenStream) -> Result<TokenStream> {
+    let mut item = match syn::parse2::<syn::ItemMod>(input.clone()) {
+        Ok(item) => item,
+        Err(_) => return Err(Error::new_spanned(input, INLINE_MODULES_ONLY)),
+    };
+    let items = match &mut item.content {
+        Some((_, items)) => items,
+        None => return Err(Error::new_spanned(item.semi, INLINE_MODULES_ONLY)),
+    };
+    let mut config = config;
+    config.push_imports(true, items.iter());
+    for item in items {
+        config.visit_item_mut(item);
+    }
+    Ok(item.into_token_stream())
+}
+
+impl Config {
+    // Adds the default error type to the arguments of `#[throws]`, when they
+    // are empty, start with `debug` or `crate = path`, or use `_` for it.
+    fn error_args(&self, args: TokenStream) -> TokenStream {
+        let error = match &self.error {
+            Some(error) => error,
+            None => return args,
+        };
+        let tokens: Vec<TokenTree> = args.clone().into_iter().collect();
+        match &tokens[..] {
+            [] => quote!(#error),
+            [TokenTree::Ident(first)] | [TokenTree::Ident(first), TokenTree::Punct(_), ..]
+                if first == "debug" && is_punct(&tokens[1..], ',') =>
+            {
+                quote!(#error, #args)
+            }
+            _ if is_crate(&tokens) => quote!(#error, #args),
+            [TokenTree::Ident(first), rest @ ..]
+                if first == "_" && (is_punct(rest, ',') || is_as(rest)) =>
+            {
+                quote!(#error #(#rest)*)
+            }
+            _ => args,
+        }
+    }
+
+    // Adds the path to culpa to some arguments that do not give one.
+    fn crate_args(&self, args: TokenStream) -> TokenStream {
+        let krate = match &self.krate {
+            Some(krate) if !has_crate(&args) => krate,
+            _ => return args,
+        };
+        match args.is_empty() {
+            true => quote!(crate = #krate),
+            false => quote!(#args, crate = #krate),
+        }
+    }
+
+    fn throws_args(&self, args: TokenStream) -> TokenStream {
+        self.crate_args(self.error_args(args))
+    }
+
+    // Adds the default error type to the arguments of `#[culpa::main]` like
+    // for `#[throws]`, and when they start with `exit_code = N`.
+    fn main_args(&self, args: TokenStream) -> TokenStream {
+        let args = match (&self.error, args.clone().into_iter().next()) {
+            (Some(error), Some(TokenTree::Ident(first))) if first == "exit_code" => {
+                quote!(#error, #args)
+            }
+            _ => self.error_args(args),
+        };
+        self.crate_args(args)
+    }
+
+    // The path to culpa, found the first time it is needed unless it is
+    // configured.
+    fn krate(&self) -> &Path {
+        self.culpa
+            .get_or_init(|| self.krate.clone().unwrap_or_else(crate::crate_path::detect))
+    }
+
+    // Whether a path names the culpa macro `name`. A name alone must be
+    // imported from culpa in the block or module it is in, or in a block
+    // around it in the same module.
+    fn is_named(&self, path: &Path, name: &str) -> bool {
+        if path.leading_colon.is_some() || path.segments.len() != 1 {
+            return crate::crate_path::is_culpa(path, name, self.krate());
+        }
+        let ident = &path.segments[0].ident;
+        for imports in self.imports.iter().rev() {
+            let mut names = imports.names.iter();
+            if names.any(|(alias, original)| alias == ident && original == name) {
+                return true;
+            }
+            if imports.others.contains(ident) {
+                return false;
+            }
+            if imports.glob && ident == name {
+                return true;
+            }
+            if imports.module {
+                break;
+            }
+        }
+        false
+    }
+
+    // Starts the scope of a module or block, with the names its `use` items
+    // import.
+    fn push_imports<'a>(&mut self, module: bool, items: impl Iterator<Item = &'a syn::Item>) {
+        let mut imports = Imports {
+            module,
+            ..Imports::default()
+        };
+        for item in items {
+            if let syn::Item::Use(item) = item {
+                self.add_imports(&mut imports, &mut Vec::new(), &item.tree);
+            }
+        }
+        self.imports.push(imports);
+    }
+
+    fn add_imports(&self, imports: &mut Imports, prefix: &mut Vec<Ident>, tree: &UseTree) {
+        match tree {
+            UseTree::Path(tree) => {
+                prefix.push(tree.ident.clone());
+                self.add_imports(imports, prefix, &tree.tree);
+                prefix.pop();
+            }
+            UseTree::Name(tree) if self.is_krate(prefix) => {
+                imports.names.push((tree.ident.clone(), tree.ident.clone()));
+            }
+            UseTree::Rename(tree) if self.is_krate(prefix) => {
+                imports
+                    .names
+                    .push((tree.rename.clone(), tree.ident.clone()));
+            }
+            UseTree::Name(tree) => imports.others.push(tree.ident.clone()),
+            UseTree::Rename(tree) => imports.others.push(tree.rename.clone()),
+            UseTree::Glob(_) => imports.glob |= self.is_krate(prefix),
+            UseTree::Group(group) => {
+                for tree in &group.items {
+                    self.add_imports(imports, prefix, tree);
+                }
+            }
+        }
+    }
+
+    // Whether the path of a `use` item is `culpa` or the path to culpa.
+    fn is_krate(&self, prefix: &[Ident]) -> bool {
+        let krate = &self.krate().segments;
+        match prefix {
+            [culpa] if culpa == "culpa" => true,
+            _ => {
+                prefix.len() == krate.len()
+                    && prefix
+                        .iter()
+                        .zip(krate)
+                        .all(|(ident, krate)| *ident == krate.ident)
+            }
+        }
+    }
+}
+
+impl VisitMut for Config {
+    fn visit_item_mod_mut(&mut self, i: &mut syn::ItemMod) {
+        if !i
+            .attrs
+            .iter()
+            .any(|attr| self.is_named(attr.path(), "config"))
+        {
+            let items = i.content.iter().flat_map(|(_, items)| items);
+            self.push_imports(true, items);
+            syn::visit_mut::visit_item_mod_mut(self, i);
+            self.imports.pop();
+        }
+    }
+
+    fn visit_block_mut(&mut self, i: &mut syn::Block) {
+        let items = i.stmts.iter().filter_map(|stmt| match stmt {
+            syn::Stmt::Item(item) => Some(item),
+            _ => None,
+        });
+        self.push_imports(false, items);
+        syn::visit_mut::visit_block_mut(self, i);
+        self.imports.pop();
+    }
+
+    fn visit_attribute_mut(&mut self, i: &mut syn::Attribute) {
+        let path = i.path().clone();
+        let args = match &i.meta {
+            syn::Meta::Path(_) => TokenStream::new(),
+            syn::Meta::List(list) => list.tokens.clone(),
+            syn::Meta::NameValue(_) => return,
+        };
+        let args = if self.is_named(&path, "throws") {
+            self.throws_args(args)
+        } else if self.is_named(&path, "try_fn") {
+            self.crate_args(args)
+        } else if self.is_named(&path, "main") {
+            self.main_args(args)
+        } else {
+            return;
+        };
+        if !args.is_empty() {
+            i.meta = syn::parse_quote!(#path(#args));
+        }
+    }
+
+    fn visit_macro_mut(&mut self, i: &mut syn::Macro) {
+        let try_block = self.is_named(&i.path, "try_block");
+        if !try_block
+            && !self.is_named(&i.path, "throws_closure")
+            && !self.is_named(&i.path, "throws_async")
+        {
+            return;
+        }
+        let tokens: Vec<TokenTree> = i.tokens.clone().into_iter().collect();
+        let arrow = (0..tokens.len()).find(|index| is_arrow(&tokens[*index..]));
+        let (args, item) = match arrow {
+            Some(index) => (&tokens[..index], &tokens[index + 2..]),
+            None => (&[][..], &tokens[..]),
+        };
+        // `try_block!(Option => ...)` has no error type, only the options
+        let option = try_block
+            && matches!(args.first(), Some(TokenTree::Ident(ident)) if ident == "Option")
+            && is_punct(&args[1..], ',');
+        let args = args.iter().cloned().collect();
+        let args = match option {
+            true => self.crate_args(args),
+            false => self.throws_args(args),
+        };
+        let item = item.iter();
+        if !args.is_empty() {
+            i.tokens = quote!(#args => #(#item)*);
+        }
+    }
+}
+
+// Whether the tokens are empty or start with the given punctuation.
+fn is_punct(tokens: &[TokenTree], ch: char) -> bool {
+    match tokens.first() {
+        Some(TokenTree::Punct(punct)) => punct.as_char() == ch,
+        Some(_) => false,
+        None => true,
+    }
+}
+
+fn is_as(tokens: &[TokenTree]) -> bool {
+    matches!(tokens.first(), Some(TokenTree::Ident(ident)) if ident == "as")
+}
+
+// Whether the tokens start with `crate =`.
+fn is_crate(tokens: &[TokenTree]) -> bool {
+    match tokens {
+        [TokenTree::Ident(ident), TokenTree::Punct(eq), ..] => {
+            ident == "crate" && eq.as_char() == '=' && eq.spacing() == Spacing::Alone
+        }
+        _ => false,
+    }
+}
+
+// Whether the tokens start with a `=>`.
+fn is_arrow(tokens: &[TokenTree]) -> bool {
+    match tokens {
+        [TokenTree::Punct(first), TokenTree::Punct(second), ..] => {
+            first.as_char() == '=' && first.spacing() == Spacing::Joint && second.as_char() == '>'
+        }
+        _ => false,
+    }
+}
+
+// Whether some arguments already give a `crate = path`.
+fn has_crate(args: &TokenStream) -> bool {
+    let tokens: Vec<TokenTree> = args.clone().into_iter().collect();
+    (0..tokens.len()).any(|index| is_crate(&tokens[index..]))
+}
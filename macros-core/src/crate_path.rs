@@ -0,0 +1,45 @@
+// This module finds the path to culpa that the generated code refers to when
+// it is not given with a `crate = path` argument.
+//
+// The dependency on culpa is looked up in the manifest of the crate being
+// compiled, so that it is found when renamed. When it cannot be found, as
+// when culpa is only a dependency of a crate re-exporting it or when the
+// transformations are called outside of a build, `::culpa` is used.
+//
+// It also tells which macro and attribute paths name culpa's own items, so
+// that items of the same name from other crates, like `anyhow::bail!`, are
+// left alone.
+
+use proc_macro_crate::FoundCrate;
+
+pub(crate) fn detect() -> syn::Path {
+    match proc_macro_crate::crate_name("culpa") {
+        Ok(FoundCrate::Name(name)) => {
+            let name = syn::Ident::new(&name, proc_macro2::Span::call_site());
+            syn::parse_quote!(::#name)
+        }
+        // Culpa's own tests and examples use it like any other crate
+        Ok(FoundCrate::Itself) | Err(_) => syn::parse_quote!(::culpa),
+    }
+}
+
+// Whether a path names the culpa item `name`: the bare name, as when it is
+// imported, or the name under `culpa` or under the given path to culpa.
+pub(crate) fn is_culpa(path: &syn::Path, name: &str, krate: &syn::Path) -> bool {
+    let segments: Vec<&syn::PathSegment> = path.segments.iter().collect();
+    let prefix = match segments.split_last() {
+        Some((last, prefix)) if last.ident == name => prefix,
+        _ => return false,
+    };
+    match prefix {
+        [] => path.leading_colon.is_none(),
+        [culpa] if culpa.ident == "culpa" => true,
+        _ => {
+            prefix.len() == krate.segments.len()
+                && prefix
+                    .iter()
+                    .zip(&krate.segments)
+                    .all(|(segment, krate)| segment.ident == krate.ident)
+        }
+    }
+}
@@ -0,0 +1,57 @@
+// With a `debug` argument, a macro shows the code it generates in a compile
+// error, since type errors in generated code refer to code that was never
+// written.
+//
+// The generated code is still output after the error, so that errors in it
+// are reported as usual next to its expansion.
+
+use proc_macro2::{Span, TokenStream};
+
+// Adds the expansion of an item to the output if debugging is enabled.
+pub fn item(debug: Option<Span>, name: &str, output: TokenStream) -> TokenStream {
+    match debug {
+        Some(debug) => {
+            let error = error(debug, name, &output).into_compile_error();
+            quote::quote!(#error #output)
+        }
+        None => output,
+    }
+}
+
+// Adds the expansion of an expression to the output if debugging is enabled.
+pub fn expr(debug: Option<Span>, name: &str, output: TokenStream) -> TokenStream {
+    match debug {
+        Some(debug) => {
+            let error = error(debug, name, &output).into_compile_error();
+            quote::quote!({ #error #output })
+        }
+        None => output,
+    }
+}
+
+pub fn error(debug: Span, name: &str, output: &TokenStream) -> syn::Error {
+    let message = format!("expansion of {}:\n\n{}", name, pretty(output));
+    syn::Error::new(debug, message)
+}
+
+// Formats generated items or an expression like rustfmt would.
+pub fn pretty(tokens: &TokenStream) -> String {
+    if let Ok(file) = syn::parse2::<syn::File>(tokens.clone()) {
+        return prettyplease::unparse(&file);
+    }
+    let expr = match syn::parse2::<syn::Expr>(tokens.clone()) {
+        Ok(expr) => expr,
+        Err(_) => return tokens.to_string(),
+    };
+    // Only whole files can be formatted, so the expression is formatted as
+    // the body of a function which is then removed again
+    let file: syn::File = syn::parse_quote!(fn expansion() { #expr });
+    let file = prettyplease::unparse(&file);
+    let body = file
+        .strip_prefix("fn expansion() {\n")
+        .and_then(|file| file.strip_suffix("}\n"))
+        .unwrap_or(&file);
+    body.lines()
+        .map(|line| line.strip_prefix("    ").unwrap_or(line))
+        .fold(String::new(), |pretty, line| pretty + line + "\n")
+}
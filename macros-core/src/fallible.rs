@@ -0,0 +1,153 @@
+// This module implements the Fallible derive.
+//
+// It implements the `Succeed`, `Throw` and `ThrowEmpty` traits for an enum by
+// constructing the variants marked with `#[culpa(ok)]`, `#[culpa(err)]` and
+// `#[culpa(empty)]` respectively. The path to culpa can be given on the enum
+// with `#[culpa(crate = path)]`.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Data, DeriveInput, Error, Fields, Path, Result, Token, Variant};
+
+pub fn derive(input: DeriveInput) -> Result<TokenStream> {
+    let variants = match &input.data {
+        Data::Enum(data) => &data.variants,
+        _ => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "#[derive(Fallible)] can only be applied to enums",
+            ))
+        }
+    };
+
+    let mut krate = None;
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("culpa"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if !meta.path.is_ident("crate") {
+                return Err(meta.error("expected `crate = path`"));
+            }
+            if krate.is_some() {
+                return Err(meta.error("the path to culpa is already given"));
+            }
+            meta.input.parse::<Token![=]>()?;
+            krate = Some(Path::parse_mod_style(meta.input)?);
+            Ok(())
+        })?;
+    }
+
+    let mut ok = None;
+    let mut err = None;
+    let mut empty = None;
+    for variant in variants {
+        for attr in variant
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("culpa"))
+        {
+            attr.parse_nested_meta(|meta| {
+                let (slot, name) = if meta.path.is_ident("ok") {
+                    (&mut ok, "ok")
+                } else if meta.path.is_ident("err") {
+                    (&mut err, "err")
+                } else if meta.path.is_ident("empty") {
+                    (&mut empty, "empty")
+                } else {
+                    return Err(meta.error("expected `ok`, `err` or `empty`"));
+                };
+                if slot.is_some() {
+                    return Err(meta.error(format!(
+                        "only one variant can be marked with #[culpa({})]",
+                        name
+                    )));
+                }
+                *slot = Some(variant);
+                Ok(())
+            })?;
+        }
+    }
+
+    if ok.is_none() && err.is_none() && empty.is_none() {
+        return Err(Error::new_spanned(
+            &input.ident,
+            "expected a variant marked with #[culpa(ok)], #[culpa(err)] or #[culpa(empty)]",
+        ));
+    }
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let krate = krate.unwrap_or_else(crate::crate_path::detect);
+    let mut output = TokenStream::new();
+
+    if let Some(variant) = ok {
+        let (ty, construct) = constructor(variant)?;
+        output.extend(quote! {
+            impl #impl_generics #krate::Succeed for #name #ty_generics #where_clause {
+                type Ok = #ty;
+
+                fn from_ok(ok: Self::Ok) -> Self {
+                    let __value = ok;
+                    #name::#construct
+                }
+            }
+        });
+    }
+
+    if let Some(variant) = err {
+        let (ty, construct) = constructor(variant)?;
+        output.extend(quote! {
+            impl #impl_generics #krate::Throw for #name #ty_generics #where_clause {
+                type Error = #ty;
+
+                fn from_error(error: Self::Error) -> Self {
+                    let __value = error;
+                    #name::#construct
+                }
+            }
+        });
+    }
+
+    if let Some(variant) = empty {
+        let ident = &variant.ident;
+        if !matches!(variant.fields, Fields::Unit) {
+            return Err(Error::new_spanned(
+                &variant.fields,
+                "variants marked with #[culpa(empty)] must not have any fields",
+            ));
+        }
+        output.extend(quote! {
+            impl #impl_generics #krate::ThrowEmpty for #name #ty_generics #where_clause {
+                fn empty() -> Self {
+                    #name::#ident
+                }
+            }
+        });
+    }
+
+    Ok(output)
+}
+
+// Returns the type wrapped by the variant, and an expression constructing the
+// variant from a `__value` of that type.
+fn constructor(variant: &Variant) -> Result<(TokenStream, TokenStream)> {
+    let ident = &variant.ident;
+    match &variant.fields {
+        Fields::Unit => Ok((quote!(()), quote!(#ident))),
+        Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+            let ty = &fields.unnamed[0].ty;
+            Ok((quote!(#ty), quote!(#ident(__value))))
+        }
+        Fields::Named(fields) if fields.named.len() == 1 => {
+            let ty = &fields.named[0].ty;
+            let field = &fields.named[0].ident;
+            Ok((quote!(#ty), quote!(#ident { #field: __value })))
+        }
+        fields => Err(Error::new_spanned(
+            fields,
+            "variants marked with #[culpa(ok)] or #[culpa(err)] must have at most one field",
+        )),
+    }
+}
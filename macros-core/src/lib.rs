@@ -0,0 +1,223 @@
+//! The transformations behind the `culpa` macros, for use in other proc macros and tools.
+//!
+//! Every macro of `culpa-macros` is a thin wrapper around the function of the same name here,
+//! which works on `proc_macro2` token streams so that it can also be called outside of a proc
+//! macro, by tests and tooling.
+//!
+//! Other attribute macros can transform functions the same way `#[throws]` does with the
+//! [`Throws`] folder, for example to Ok-wrap the bodies of the functions they generate. The code
+//! it generates refers to `culpa` by the name it has in the manifest of the crate being compiled,
+//! so crates using those macros must depend on `culpa` too, unless they are given another path to
+//! it with [`Throws::with_crate`] or a `crate = path` argument.
+//!
+//! ```
+//! use culpa_macros_core::{Args, Throws};
+//! use syn::fold::Fold;
+//!
+//! let args: Args = syn::parse_quote!(Error);
+//! let item: syn::ItemFn = syn::parse_quote! {
+//!     fn answer() -> u8 {
+//!         42
+//!     }
+//! };
+//! let mut throws = Throws::new(Some(args));
+//! let item = throws.fold_item_fn(item);
+//! let output = throws.finish(item).unwrap();
+//! ```
+//!
+//! [`expand`] shows what the `#[throws]` and `#[try_fn]` attributes in some source code expand
+//! to, like `cargo expand` does for a whole crate:
+//!
+//! ```
+//! let expanded = culpa_macros_core::expand(
+//!     "#[throws(Error)]
+//!     fn answer() -> u8 {
+//!         42
+//!     }",
+//! );
+//! assert!(expanded.contains("fn answer() -> ::core::result::Result<u8, Error> {"));
+//! ```
+//!
+//! With the `trace` feature, `?` is transformed to record where errors propagate through, as with
+//! the `trace` feature of `culpa`.
+
+mod args;
+mod config;
+mod crate_path;
+mod debug;
+mod fallible;
+mod report;
+mod sum;
+mod throws;
+mod warning;
+
+use proc_macro2::TokenStream;
+use quote::ToTokens;
+use syn::fold::Fold;
+use syn::Result;
+
+use args::{ArgsAnd, TryBlock, TryFnArgs};
+
+pub use args::Args;
+pub use throws::Throws;
+
+/// Expands `#[throws]` with the given arguments on an item.
+pub fn throws(args: TokenStream, input: TokenStream) -> Result<TokenStream> {
+    let args: Args = syn::parse2(args)?;
+    let debug = args.debug();
+    let output = match args.errors() {
+        Some(_) => sum::throws(args, input)?,
+        None => Throws::new(Some(args)).fold(input)?,
+    };
+    Ok(debug::item(debug, "#[throws]", output))
+}
+
+/// Expands `#[try_fn]` with the given arguments on an item.
+pub fn try_fn(args: TokenStream, input: TokenStream) -> Result<TokenStream> {
+    let args: TryFnArgs = syn::parse2(args)?;
+    let mut throws = Throws::new(None);
+    if let Some(krate) = args.krate() {
+        throws = throws.with_crate(krate.clone());
+    }
+    let output = throws.fold(input)?;
+    Ok(debug::item(args.debug(), "#[try_fn]", output))
+}
+
+/// Expands `#[nothrow]`, which is an error unless the item is inside an item with `#[throws]`
+/// or `#[try_fn]`.
+pub fn nothrow(args: TokenStream, input: TokenStream) -> Result<TokenStream> {
+    // Marked by the throws attribute when it skips the function
+    if args.to_string() == "__culpa_checked" {
+        return Ok(input);
+    }
+    let error = syn::Error::new_spanned(
+        &input,
+        "#[nothrow] can only be applied to functions inside a #[throws] or #[try_fn] impl block, trait or module",
+    );
+    let error = error.to_compile_error();
+    Ok(quote::quote!(#error #input))
+}
+
+/// Expands `throws_closure!`.
+pub fn throws_closure(input: TokenStream) -> Result<TokenStream> {
+    let ArgsAnd { args, item } = syn::parse2::<ArgsAnd<syn::ExprClosure>>(input)?;
+    let debug = args.debug();
+    let mut throws = Throws::new(Some(args));
+    let closure = throws.fold_closure(item);
+    let output = throws.finish(closure)?;
+    Ok(debug::expr(debug, "throws_closure!", output))
+}
+
+/// Expands `throws_async!`.
+pub fn throws_async(input: TokenStream) -> Result<TokenStream> {
+    let ArgsAnd { args, item } = syn::parse2::<ArgsAnd<syn::ExprAsync>>(input)?;
+    let debug = args.debug();
+    let mut throws = Throws::new(Some(args));
+    let block = throws.fold_async(item);
+    let output = throws.finish(block)?;
+    Ok(debug::expr(debug, "throws_async!", output))
+}
+
+/// Expands `try_block!`.
+pub fn try_block(input: TokenStream) -> Result<TokenStream> {
+    let TryBlock { args, block } = syn::parse2(input)?;
+    let debug = args.debug();
+    let mut throws = Throws::new(Some(args));
+    let block = throws.fold_try_block(block);
+    let output = throws.finish(block)?;
+    Ok(debug::expr(debug, "try_block!", output))
+}
+
+/// Expands `#[culpa::main]` with the given arguments on a function.
+pub fn main(args: TokenStream, input: TokenStream) -> Result<TokenStream> {
+    let args = syn::parse2(args)?;
+    let item = syn::parse2(input)?;
+    report::main(args, item)
+}
+
+/// Expands `#[culpa::config]` with the given arguments on an inline module.
+pub fn config(args: TokenStream, input: TokenStream) -> Result<TokenStream> {
+    config::config(syn::parse2(args)?, input)
+}
+
+/// Expands `#[derive(Fallible)]`.
+pub fn derive_fallible(input: TokenStream) -> Result<TokenStream> {
+    fallible::derive(syn::parse2(input)?)
+}
+
+/// Expands the `#[throws]` and `#[try_fn]` attributes in a source file, and formats the result.
+///
+/// Attributes are recognized by their name alone, however they are imported. Errors are
+/// expanded to `compile_error!` invocations, as they would be by the compiler.
+pub fn expand(source: &str) -> String {
+    let output = match syn::parse_file(source) {
+        Ok(file) => Expand.fold_file(file).into_token_stream(),
+        Err(error) => error.into_compile_error(),
+    };
+    debug::pretty(&output)
+}
+
+// Replaces every annotated item with its expansion
+struct Expand;
+
+impl Fold for Expand {
+    fn fold_item(&mut self, mut i: syn::Item) -> syn::Item {
+        let attr = match &mut i {
+            syn::Item::Fn(item) => take_attr(&mut item.attrs),
+            syn::Item::Impl(item) => take_attr(&mut item.attrs),
+            syn::Item::Trait(item) => take_attr(&mut item.attrs),
+            syn::Item::Mod(item) => take_attr(&mut item.attrs),
+            _ => None,
+        };
+        match attr {
+            Some(attr) => syn::Item::Verbatim(expand_attr(attr, i.into_token_stream())),
+            None => syn::fold::fold_item(self, i),
+        }
+    }
+
+    fn fold_impl_item(&mut self, mut i: syn::ImplItem) -> syn::ImplItem {
+        let attr = match &mut i {
+            syn::ImplItem::Fn(item) => take_attr(&mut item.attrs),
+            _ => None,
+        };
+        match attr {
+            Some(attr) => syn::ImplItem::Verbatim(expand_attr(attr, i.into_token_stream())),
+            None => syn::fold::fold_impl_item(self, i),
+        }
+    }
+
+    fn fold_trait_item(&mut self, mut i: syn::TraitItem) -> syn::TraitItem {
+        let attr = match &mut i {
+            syn::TraitItem::Fn(item) => take_attr(&mut item.attrs),
+            _ => None,
+        };
+        match attr {
+            Some(attr) => syn::TraitItem::Verbatim(expand_attr(attr, i.into_token_stream())),
+            None => syn::fold::fold_trait_item(self, i),
+        }
+    }
+}
+
+// Removes the first `#[throws]` or `#[try_fn]` attribute
+fn take_attr(attrs: &mut Vec<syn::Attribute>) -> Option<syn::Attribute> {
+    let index = attrs
+        .iter()
+        .position(|attr| attr_name(attr) == "throws" || attr_name(attr) == "try_fn")?;
+    Some(attrs.remove(index))
+}
+
+fn expand_attr(attr: syn::Attribute, item: TokenStream) -> TokenStream {
+    let args = match &attr.meta {
+        syn::Meta::List(list) => list.tokens.clone(),
+        _ => TokenStream::new(),
+    };
+    let output = match attr_name(&attr) == "throws" {
+        true => throws(args, item),
+        false => try_fn(args, item),
+    };
+    output.unwrap_or_else(syn::Error::into_compile_error)
+}
+
+fn attr_name(attr: &syn::Attribute) -> &syn::Ident {
+    &attr.path().segments.last().unwrap().ident
+}
@@ -0,0 +1,132 @@
+// This module implements the `main` attribute.
+//
+// It moves the body of the function into a throwing closure, or a throwing
+// async block for an async function, and wraps its result in a `Report` with
+// the configured exit code.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::parse::{Parse, ParseStream};
+use syn::{Error, LitInt, Path, Result, Token, Type};
+
+use crate::throws::Throws;
+use crate::Args;
+
+mod kw {
+    syn::custom_keyword!(exit_code);
+}
+
+pub struct MainArgs {
+    error: Type,
+    exit_code: u8,
+    krate: Option<Path>,
+}
+
+impl Parse for MainArgs {
+    fn parse(input: ParseStream) -> Result<MainArgs> {
+        let options = input.peek(kw::exit_code) || input.peek(Token![crate]);
+        let error = match input.is_empty() || options {
+            true => syn::parse_quote!(Error),
+            false => {
+                let error = match input.parse()? {
+                    Type::Infer(_) => syn::parse_quote!(Error),
+                    error => error,
+                };
+                if !input.is_empty() {
+                    input.parse::<Token![,]>()?;
+                }
+                error
+            }
+        };
+        let mut exit_code = None;
+        let mut krate = None;
+        while !input.is_empty() {
+            if input.peek(kw::exit_code) && exit_code.is_none() {
+                input.parse::<kw::exit_code>()?;
+                input.parse::<Token![=]>()?;
+                let code = input.parse::<LitInt>()?;
+                if code.base10_parse::<u8>()? == 0 {
+                    return Err(Error::new_spanned(
+                        code,
+                        "the exit code cannot be 0, which would report success",
+                    ));
+                }
+                exit_code = Some(code.base10_parse()?);
+            } else if input.peek(Token![crate]) && krate.is_none() {
+                input.parse::<Token![crate]>()?;
+                input.parse::<Token![=]>()?;
+                krate = Some(Path::parse_mod_style(input)?);
+            } else {
+                return Err(input.error(
+                    "unexpected argument, expected `Error`, `exit_code = N` or `crate = path`",
+                ));
+            }
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
+        }
+        Ok(MainArgs {
+            error,
+            exit_code: exit_code.unwrap_or(1),
+            krate,
+        })
+    }
+}
+
+pub fn main(args: MainArgs, item: syn::ItemFn) -> Result<TokenStream> {
+    let syn::ItemFn {
+        attrs,
+        vis,
+        sig,
+        block,
+    } = item;
+    if let Some(arg) = sig.inputs.first() {
+        return Err(Error::new_spanned(
+            arg,
+            "#[culpa::main] functions cannot take arguments",
+        ));
+    }
+
+    let MainArgs {
+        error,
+        exit_code,
+        krate,
+    } = args;
+    let ok = match &sig.output {
+        syn::ReturnType::Type(_, ty) => quote!(#ty),
+        syn::ReturnType::Default => quote!(()),
+    };
+    let krate = krate.unwrap_or_else(crate::crate_path::detect);
+    let output = quote!(-> #krate::Report<#ok, #error>);
+    let mut throws = Throws::new(Some(Args::default().with_error(error))).with_crate(krate.clone());
+    let result = match sig.asyncness {
+        Some(_) => {
+            let block = throws.fold_async(syn::parse_quote!(async move #block));
+            quote!(#block.await)
+        }
+        None => {
+            let closure = throws.fold_closure(syn::parse_quote!(move || -> #ok #block));
+            quote!((#closure)())
+        }
+    };
+    let syn::Signature {
+        constness,
+        asyncness,
+        unsafety,
+        abi,
+        fn_token,
+        ident,
+        generics,
+        ..
+    } = sig;
+    let where_clause = &generics.where_clause;
+    throws.finish(quote! {
+        #(#attrs)*
+        #vis #constness #asyncness #unsafety #abi #fn_token #ident #generics() #output #where_clause {
+            #krate::Report::with_exit_code(
+                #result,
+                ::core::num::NonZeroU8::new(#exit_code).unwrap(),
+            )
+        }
+    })
+}
@@ -0,0 +1,179 @@
+// This module implements `#[throws(A | B)]`.
+//
+// It generates an enum with a variant for each of the error types, named
+// after the function, and uses it as the error type of the function. The enum
+// is generic over the error types, with each parameter defaulting to its
+// type, so that `Display` and `Error` can be implemented only when all of the
+// error types implement them. The `From` impls are on the default
+// instantiation, since generic ones would overlap.
+//
+// The enum is named after the function in camel case followed by `Error`, and
+// is generated next to the function, so this only works for functions outside
+// of impl blocks and traits. It does not have the generic parameters of the
+// function, so the error types cannot use them.
+
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote, ToTokens};
+use syn::visit::Visit;
+use syn::{Error, Ident, Result, Type};
+
+use crate::throws::Throws;
+use crate::Args;
+
+pub const FREE_FUNCTIONS_ONLY: &str =
+    "a list of error types can only be used on free functions, which the error enum is generated next to";
+
+pub fn throws(args: Args, input: TokenStream) -> Result<TokenStream> {
+    let errors = args.errors().unwrap().clone();
+    let item_fn: syn::ItemFn = match syn::parse2(input) {
+        Ok(item_fn) => item_fn,
+        Err(_) => return Err(Error::new_spanned(errors, FREE_FUNCTIONS_ONLY)),
+    };
+    if let Some(receiver) = item_fn.sig.receiver() {
+        return Err(Error::new_spanned(receiver, FREE_FUNCTIONS_ONLY));
+    }
+
+    for ty in &errors {
+        if let Some(param) = generic_param(&item_fn.sig.generics, ty) {
+            return Err(Error::new_spanned(
+                param,
+                "the error types cannot use the generic parameters of the function, \
+                 which the error enum generated next to it does not have",
+            ));
+        }
+    }
+
+    let name = format_ident!("{}Error", camel_case(&item_fn.sig.ident));
+    let mut variants: Vec<Ident> = Vec::new();
+    for ty in &errors {
+        let variant = variant_name(ty)?;
+        if variants.contains(&variant) {
+            return Err(Error::new_spanned(
+                ty,
+                format!(
+                    "each error type must have a different name, there is already a `{}`",
+                    variant
+                ),
+            ));
+        }
+        variants.push(variant);
+    }
+    let params: Vec<Ident> = (0..variants.len())
+        .map(|i| Ident::new(&format!("__E{}", i), Span::call_site()))
+        .collect();
+    let types: Vec<&Type> = errors.iter().collect();
+
+    let vis = item_fn.vis.clone();
+    let doc = format!("The errors thrown by [`{}`].", item_fn.sig.ident);
+    let item = Throws::new(Some(args.with_error(syn::parse_quote!(#name))))
+        .fold(item_fn.into_token_stream())?;
+
+    Ok(quote! {
+        #[doc = #doc]
+        #vis enum #name<#(#params = #types),*> {
+            #(#variants(#params),)*
+        }
+
+        #(
+            impl ::core::convert::From<#types> for #name {
+                fn from(error: #types) -> Self {
+                    #name::#variants(error)
+                }
+            }
+        )*
+
+        impl<#(#params: ::core::fmt::Debug),*> ::core::fmt::Debug for #name<#(#params),*> {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                match self {
+                    #(#name::#variants(error) => ::core::fmt::Debug::fmt(error, f),)*
+                }
+            }
+        }
+
+        impl<#(#params: ::core::fmt::Display),*> ::core::fmt::Display for #name<#(#params),*> {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                match self {
+                    #(#name::#variants(error) => ::core::fmt::Display::fmt(error, f),)*
+                }
+            }
+        }
+
+        impl<#(#params: ::core::error::Error),*> ::core::error::Error for #name<#(#params),*> {
+            fn source(&self) -> ::core::option::Option<&(dyn ::core::error::Error + 'static)> {
+                match self {
+                    #(#name::#variants(error) => error.source(),)*
+                }
+            }
+        }
+
+        #item
+    })
+}
+
+// A generic parameter of the function that an error type uses.
+fn generic_param(generics: &syn::Generics, ty: &Type) -> Option<TokenStream> {
+    struct Uses<'a> {
+        generics: &'a syn::Generics,
+        found: Option<TokenStream>,
+    }
+
+    impl<'ast> Visit<'ast> for Uses<'_> {
+        fn visit_path(&mut self, i: &'ast syn::Path) {
+            let first = &i.segments[0].ident;
+            let type_param = self
+                .generics
+                .type_params()
+                .any(|param| param.ident == *first);
+            let const_param = self
+                .generics
+                .const_params()
+                .any(|param| param.ident == *first);
+            if i.leading_colon.is_none() && (type_param || const_param) && self.found.is_none() {
+                self.found = Some(i.to_token_stream());
+            }
+            syn::visit::visit_path(self, i);
+        }
+
+        fn visit_lifetime(&mut self, i: &'ast syn::Lifetime) {
+            let mut params = self.generics.lifetimes();
+            if params.any(|param| param.lifetime == *i) && self.found.is_none() {
+                self.found = Some(i.to_token_stream());
+            }
+        }
+    }
+
+    let mut uses = Uses {
+        generics,
+        found: None,
+    };
+    uses.visit_type(ty);
+    uses.found
+}
+
+// The name of the variant for an error type, which is the name of the type,
+// or the name of its module for types just called `Error`, like `io::Error`.
+fn variant_name(ty: &Type) -> Result<Ident> {
+    let path = match ty {
+        Type::Path(ty) if ty.qself.is_none() => &ty.path,
+        _ => return Err(Error::new_spanned(ty, "expected the path of an error type")),
+    };
+    let mut segments = path.segments.iter().rev();
+    let last = &segments.next().unwrap().ident;
+    match segments.next() {
+        Some(module) if last == "Error" => Ok(Ident::new(&camel_case(&module.ident), last.span())),
+        _ => Ok(last.clone()),
+    }
+}
+
+fn camel_case(ident: &Ident) -> String {
+    let ident = ident.to_string();
+    let mut camel = String::new();
+    for word in ident.trim_start_matches("r#").split('_') {
+        let mut chars = word.chars();
+        if let Some(first) = chars.next() {
+            camel.extend(first.to_uppercase());
+            camel.push_str(chars.as_str());
+        }
+    }
+    camel
+}
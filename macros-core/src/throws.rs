@@ -0,0 +1,1047 @@
+// This module implements the Throws folder.
+//
+// The Throws folder actually visits the item being processed and performs two
+// processes:
+// - It ok wraps return expressions and inserts terminal Ok(())s.
+// - It delegates return type rewriting to the Args type.
+//
+// Closures and async blocks inside the body are left alone unless they are
+// annotated with `#[throws]` or `#[try_fn]`, in which case a fresh Throws
+// folder is used to transform them as if they were their own function. With a
+// `debug` argument, their expansion is reported as an error, see the debug
+// module.
+//
+// When the error type is an `impl Trait`, thrown errors cannot be converted
+// with `From` because the target of the conversion is exactly what is being
+// inferred. In that case `?` and `throw!` in the body are rewritten to throw
+// their errors unconverted, so the opaque type is the thrown error type.
+//
+// With the `trace` feature, `?` is rewritten to add its location to the
+// trace of the error as it propagates, when the error is `Traced`.
+//
+// The generated code refers to culpa by the path given with a `crate = path`
+// argument, or the one found by the crate_path module. Annotated closures and
+// async blocks inside the body use the same path unless they are given one.
+//
+// With `as ResultOption`, values are wrapped in `Some` before being ok wrapped
+// and `?` is always rewritten, so that it works on both options and results:
+// a `None` returns `Ok(None)` and an error is thrown as usual.
+//
+// In a try block, `?` and `throw!` are rewritten to break out of the block
+// with the error instead of returning it, and `return` is left alone, having
+// been ok wrapped by the folder of the function around the block. The `?`s
+// in the arguments of the standard macros taking expressions are rewritten
+// too, and those in the arguments of other macros are reported as errors, as
+// are unlabeled `break`s and `continue`s for loops outside of the block.
+//
+// `throw!` and the other macros are only rewritten when their paths name
+// culpa's own macros, so that macros of the same name from other crates, like
+// `anyhow::bail!`, are left alone.
+//
+// Explicitly wrapped tail and return expressions, like `Ok(x)`, are warned
+// about, see the warning module.
+//
+// When applied to an impl block, trait or inline module, every function
+// inside is transformed with its own fresh Throws folder, except those marked
+// with `#[nothrow]` or with their own `#[throws]` or `#[try_fn]` attribute.
+
+use std::cell::OnceCell;
+
+use proc_macro2::{Span, TokenStream, TokenTree};
+use quote::ToTokens;
+use syn::fold::Fold;
+use syn::parse::Parser;
+use syn::punctuated::{Pair, Punctuated};
+
+use crate::args::TryFnArgs;
+use crate::Args;
+
+// Whether `?` is rewritten to add its location to the trace of `Traced`
+// errors, which is enabled by the `trace` feature of culpa.
+const TRACE: bool = cfg!(feature = "trace");
+
+/// The folder transforming a function, closure, async block or try block like `#[throws]` does.
+///
+/// A function is transformed with the methods of [`Fold`] for it, like [`Fold::fold_item_fn`],
+/// and the others with the methods of the same name on `Throws`. Each `Throws` transforms a single
+/// item, after which [`Throws::finish`] returns it or the errors found while transforming it.
+pub struct Throws {
+    args: Option<Args>,
+    krate: OnceCell<syn::Path>,
+    outer_fn: bool,
+    return_type: syn::Type,
+    ok_type: syn::Type,
+    exact_errors: bool,
+    optional: bool,
+    future: bool,
+    try_block: Option<syn::Lifetime>,
+    loops: usize,
+    generics: Vec<syn::Ident>,
+    warnings: Vec<TokenStream>,
+    error: Option<syn::Error>,
+}
+
+impl Throws {
+    /// Creates a folder with the arguments of `#[throws]`, or `None` to behave like `#[try_fn]`.
+    pub fn new(args: Option<Args>) -> Throws {
+        let krate = match args.as_ref().and_then(Args::krate) {
+            Some(krate) => OnceCell::from(krate.clone()),
+            None => OnceCell::new(),
+        };
+        Throws {
+            args,
+            krate,
+            outer_fn: true,
+            return_type: syn::parse_quote!(()),
+            ok_type: syn::parse_quote!(_),
+            exact_errors: false,
+            optional: false,
+            future: false,
+            try_block: None,
+            loops: 0,
+            generics: Vec::new(),
+            warnings: Vec::new(),
+            error: None,
+        }
+    }
+
+    /// Uses the given path to culpa in the generated code, as the `crate = path` argument does.
+    pub fn with_crate(self, krate: syn::Path) -> Throws {
+        Throws {
+            krate: OnceCell::from(krate),
+            ..self
+        }
+    }
+
+    // The path to culpa, found from the manifest the first time it is needed
+    // unless it was given.
+    fn krate(&self) -> &syn::Path {
+        self.krate.get_or_init(crate::crate_path::detect)
+    }
+
+    /// Transforms a function, method, impl block, trait or inline module, as the attributes do.
+    pub fn fold(&mut self, input: TokenStream) -> syn::Result<TokenStream> {
+        if let Ok(item_fn) = syn::parse2(input.clone()) {
+            let item_fn = self.fold_item_fn(item_fn);
+            self.finish(item_fn)
+        } else if let Ok(impl_item_fn) = syn::parse2(input.clone()) {
+            let impl_item_fn = self.fold_impl_item_fn(impl_item_fn);
+            self.finish(impl_item_fn)
+        } else if let Ok(trait_item_fn) = syn::parse2(input.clone()) {
+            let trait_item_fn = self.fold_trait_item_fn(trait_item_fn);
+            self.finish(trait_item_fn)
+        } else if let Ok(mut item_impl) = syn::parse2::<syn::ItemImpl>(input.clone()) {
+            self.add_generics(&item_impl.generics);
+            for item in &mut item_impl.items {
+                if let syn::ImplItem::Fn(item_fn) = item {
+                    if should_fold(&mut item_fn.attrs) {
+                        let item = item_fn.clone();
+                        *item_fn = self.fold_nested(|throws| throws.fold_impl_item_fn(item));
+                    }
+                }
+            }
+            self.finish(item_impl)
+        } else if let Ok(mut item_trait) = syn::parse2::<syn::ItemTrait>(input.clone()) {
+            self.add_generics(&item_trait.generics);
+            for item in &mut item_trait.items {
+                if let syn::TraitItem::Fn(item_fn) = item {
+                    if should_fold(&mut item_fn.attrs) {
+                        let item = item_fn.clone();
+                        *item_fn = self.fold_nested(|throws| throws.fold_trait_item_fn(item));
+                    }
+                }
+            }
+            self.finish(item_trait)
+        } else if let Ok(mut item_mod) = syn::parse2::<syn::ItemMod>(input.clone()) {
+            let items = match &mut item_mod.content {
+                Some((_, items)) => items,
+                None => {
+                    return Err(syn::Error::new_spanned(
+                        item_mod.semi,
+                        format!("#[{}] can only be applied to inline modules", self.name()),
+                    ))
+                }
+            };
+            for item in items {
+                if let syn::Item::Fn(item_fn) = item {
+                    if should_fold(&mut item_fn.attrs) {
+                        let item = item_fn.clone();
+                        *item_fn = self.fold_nested(|throws| throws.fold_item_fn(item));
+                    }
+                }
+            }
+            self.finish(item_mod)
+        } else {
+            Err(syn::Error::new_spanned(
+                input,
+                format!(
+                    "#[{}] can only be applied to functions, methods, impl blocks, traits and modules",
+                    self.name()
+                ),
+            ))
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self.args {
+            Some(_) => "throws",
+            None => "try_fn",
+        }
+    }
+
+    /// Returns the transformed item, or the errors found while transforming it.
+    pub fn finish(&mut self, item: impl ToTokens) -> syn::Result<TokenStream> {
+        match self.error.take() {
+            Some(error) => Err(error),
+            None => Ok(item.into_token_stream()),
+        }
+    }
+
+    fn push_error(&mut self, error: syn::Error) {
+        match &mut self.error {
+            Some(existing) => existing.combine(error),
+            None => self.error = Some(error),
+        }
+    }
+
+    // Transforms an item with a fresh folder using the same arguments
+    fn fold_nested<T: ToTokens>(&mut self, f: impl FnOnce(&mut Throws) -> T) -> T {
+        self.fold_nested_with(self.args.clone(), None, None, f)
+    }
+
+    // Transforms an annotated closure or async block with a fresh folder,
+    // which uses the same path to culpa unless it is given another one
+    fn fold_nested_with<T: ToTokens>(
+        &mut self,
+        args: Option<Args>,
+        debug: Option<Span>,
+        krate: Option<syn::Path>,
+        f: impl FnOnce(&mut Throws) -> T,
+    ) -> T {
+        let mut throws = Throws::new(args);
+        throws.generics = self.generics.clone();
+        throws.krate = match krate {
+            Some(krate) => OnceCell::from(krate),
+            None => self.krate.clone(),
+        };
+        let output = f(&mut throws);
+        if let Some(error) = throws.error {
+            self.push_error(error);
+        } else if let Some(debug) = debug {
+            let name = format!("#[{}]", throws.name());
+            let output = output.to_token_stream();
+            self.push_error(crate::debug::error(debug, &name, &output));
+        }
+        output
+    }
+
+    // Transforms the return type of a function. For a method that is not async
+    // but returns `impl Future`, it is the output of the future that is
+    // transformed instead. Only the signature tells whether a function is a
+    // method, by taking `self`: the attribute cannot see whether a function
+    // with a body is in an impl block, and a trait and its impls must agree.
+    fn fold_fn_output(&mut self, sig: syn::Signature) -> syn::Signature {
+        self.add_generics(&sig.generics);
+        let method = sig.receiver().is_some();
+        let mut output = sig.output;
+        if sig.asyncness.is_none() && method {
+            if let Some(ty) = future_output(&mut output) {
+                let arrow = Default::default();
+                let folded =
+                    self.fold_return_type(syn::ReturnType::Type(arrow, Box::new(ty.clone())));
+                if let syn::ReturnType::Type(_, folded) = folded {
+                    *ty = *folded;
+                }
+                self.future = true;
+                return syn::Signature { output, ..sig };
+            }
+        }
+        syn::Signature {
+            output: self.fold_return_type(output),
+            ..sig
+        }
+    }
+
+    // Adds the type parameters in scope, which the warnings about explicitly
+    // wrapped values cannot tell the types of.
+    fn add_generics(&mut self, generics: &syn::Generics) {
+        let params = generics.type_params().map(|param| param.ident.clone());
+        self.generics.extend(params);
+    }
+
+    // Transforms the body of a function, closure or block, with the warnings
+    // about explicitly wrapped values in it added to its start.
+    fn fold_body(&mut self, block: syn::Block) -> syn::Block {
+        crate::warning::tail(&self.ok_type, &self.generics, &block, &mut self.warnings);
+        let mut block = self.fold_block(block);
+        let warnings = self.warnings.drain(..);
+        let stmts = warnings.map(|warning| syn::parse2(warning).unwrap());
+        block.stmts.splice(0..0, stmts);
+        block
+    }
+
+    fn fold_fn_block(&mut self, block: syn::Block) -> syn::Block {
+        if !self.future {
+            let inner = self.fold_body(block);
+            return self.make_fn_block(&inner);
+        }
+
+        // The future is the async block the body ends with, any statements
+        // before it run when the function is called and are left alone
+        let mut block = block;
+        match block.stmts.last_mut() {
+            Some(syn::Stmt::Expr(syn::Expr::Async(expr_async), None)) => {
+                if take_throws_attr(&mut expr_async.attrs).is_some() {
+                    self.push_error(syn::Error::new_spanned(
+                        expr_async.async_token,
+                        format!(
+                            "this async block is already transformed by the #[{}] on its function",
+                            self.name()
+                        ),
+                    ));
+                }
+                let inner = self.fold_body(expr_async.block.clone());
+                expr_async.block = self.make_expr_block(&inner);
+            }
+            _ => self.push_error(syn::Error::new_spanned(
+                &block,
+                format!(
+                    "#[{}] on a method returning `impl Future` requires its body to end with an async block",
+                    self.name()
+                ),
+            )),
+        }
+        block
+    }
+
+    /// Transforms a closure, whose ok type is inferred if it has no return type.
+    pub fn fold_closure(&mut self, i: syn::ExprClosure) -> syn::ExprClosure {
+        // A closure without a return type has its ok type inferred
+        let output = match i.output {
+            syn::ReturnType::Default => syn::parse_quote!(-> _),
+            output => output,
+        };
+        let output = self.fold_return_type(output);
+
+        self.outer_fn = false;
+
+        let inner = match *i.body {
+            syn::Expr::Block(syn::ExprBlock {
+                attrs,
+                label: None,
+                block,
+            }) if attrs.is_empty() => block,
+            body => syn::parse_quote!({ #body }),
+        };
+        let inner = self.fold_body(inner);
+        let body = Box::new(syn::Expr::Block(syn::ExprBlock {
+            attrs: Vec::new(),
+            label: None,
+            block: self.make_expr_block(&inner),
+        }));
+
+        syn::ExprClosure { output, body, ..i }
+    }
+
+    /// Transforms a block into a try block, which evaluates to the wrapper type.
+    pub fn fold_try_block(&mut self, block: syn::Block) -> syn::ExprBlock {
+        self.fold_return_type(syn::parse_quote!(-> _));
+
+        self.outer_fn = false;
+
+        // Hygienic, so that it cannot be named by a `break` in the block
+        let label = syn::Lifetime::new("'__culpa_try", proc_macro2::Span::mixed_site());
+        self.try_block = Some(label.clone());
+
+        let inner = self.fold_body(block);
+        syn::ExprBlock {
+            attrs: Vec::new(),
+            label: Some(syn::parse_quote!(#label:)),
+            block: self.make_expr_block(&inner),
+        }
+    }
+
+    // Builds the value thrown by a rewritten `?` from its `error`.
+    //
+    // With tracing, the location of the `?` is added to the trace of the
+    // error, so the conversion and the tracing are spanned to it. It is
+    // skipped for an inferred return type, where whether the error is traced
+    // cannot be known. Without tracing, the conversion is not spanned to the
+    // `?`, or lints would see a conversion written by the user.
+    fn throw_error(&self, span: proc_macro2::Span) -> TokenStream {
+        let ty = &self.return_type;
+        let krate = self.krate_spanned(span);
+        if self.exact_errors {
+            return quote::quote!(<#ty as #krate::__internal::_Throw>::from_error(error));
+        }
+        if !TRACE || matches!(ty, syn::Type::Infer(_)) {
+            return quote::quote! {
+                <#ty as #krate::__internal::_Throw>::from_error(::core::convert::From::from(error))
+            };
+        }
+        quote::quote_spanned! {span=> {
+            #[allow(unused_imports)]
+            use #krate::__internal::{_TraceNoop, _TraceReturn};
+            let thrown = <#ty as #krate::__internal::_Throw>::from_error(
+                #krate::__internal::_convert(error),
+            );
+            (&#krate::__internal::_Trace::new(thrown)).__culpa_trace()
+        }}
+    }
+
+    // Rewrites a `?` in a function returning `Result<Option<T>, E>` with
+    // `as ResultOption`, where `None` returns `Ok(None)` and an error is
+    // converted and thrown. The conversion happens in `_BranchOptional`, so
+    // only the tracing is left to do here.
+    fn branch_optional(
+        &self,
+        span: proc_macro2::Span,
+        expr: &syn::Expr,
+        exit: &TokenStream,
+    ) -> TokenStream {
+        let ty = &self.return_type;
+        let krate = self.krate_spanned(span);
+        let thrown = match !TRACE || matches!(ty, syn::Type::Infer(_)) {
+            true => quote::quote!(thrown),
+            false => quote::quote_spanned! {span=> {
+                #[allow(unused_imports)]
+                use #krate::__internal::{_TraceNoop, _TraceReturn};
+                (&#krate::__internal::_Trace::new(thrown)).__culpa_trace()
+            }},
+        };
+        quote::quote_spanned! {span=>
+            match #krate::__internal::_BranchOptional::<#ty>::branch_optional(#expr) {
+                ::core::result::Result::Ok(value) => value,
+                ::core::result::Result::Err(thrown) => #exit #thrown,
+            }
+        }
+    }
+
+    // Whether `?` is rewritten rather than left to return from the function.
+    fn rewrites_try(&self) -> bool {
+        self.try_block.is_some() || self.exact_errors || self.optional || TRACE
+    }
+
+    // Rewrites the `?`s in the arguments of a macro taking expressions
+    // separated by commas, or an expression and a length like `vec![x; n]`,
+    // which is how they are found in the standard macros and culpa's own.
+    fn fold_macro_args(&mut self, tokens: TokenStream) -> TokenStream {
+        if !contains_try(tokens.clone()) {
+            return tokens;
+        }
+        let repeat = |input: syn::parse::ParseStream| {
+            let value: syn::Expr = input.parse()?;
+            let semi: syn::Token![;] = input.parse()?;
+            let len: syn::Expr = input.parse()?;
+            Ok((value, semi, len))
+        };
+        if let Ok((value, semi, len)) = repeat.parse2(tokens.clone()) {
+            let value = self.fold_expr(value);
+            let len = self.fold_expr(len);
+            return quote::quote!(#value #semi #len);
+        }
+        let parser = Punctuated::<syn::Expr, syn::Token![,]>::parse_terminated;
+        match parser.parse2(tokens.clone()) {
+            Ok(args) => {
+                let args = args.into_pairs().map(|pair| {
+                    let (expr, comma) = pair.into_tuple();
+                    Pair::new(self.fold_expr(expr), comma)
+                });
+                let args: Punctuated<syn::Expr, syn::Token![,]> = args.collect();
+                args.into_token_stream()
+            }
+            Err(_) => {
+                self.check_macro_args(&tokens);
+                tokens
+            }
+        }
+    }
+
+    // Reports a `?` in the arguments of a macro in a try block that cannot be
+    // rewritten, where it would return from the enclosing function instead of
+    // exiting the block.
+    fn check_macro_args(&mut self, tokens: &TokenStream) {
+        if self.try_block.is_none() {
+            return;
+        }
+        if let Some(span) = find_try(tokens.clone()) {
+            self.push_error(syn::Error::new(
+                span,
+                "`?` in the arguments of this macro would return from the function instead of \
+                 exiting the try block, move it out of the macro",
+            ));
+        }
+    }
+
+    // Reports an unlabeled `break` or `continue` in a try block outside of
+    // any loop in it, which would refer to a loop around the block, but
+    // cannot leave the labeled block the try block expands to.
+    fn check_unlabeled(&mut self, token: &impl ToTokens, keyword: &str) {
+        if self.try_block.is_some() && self.loops == 0 {
+            self.push_error(syn::Error::new_spanned(
+                token,
+                format!(
+                    "`{}` inside a try block needs the label of the loop it refers to, \
+                     like `'outer: for ... {{ ... {} 'outer; }}`",
+                    keyword, keyword
+                ),
+            ));
+        }
+    }
+
+    // The path to culpa spanned to a `?`, so that the location of a call
+    // through it is the `?` too.
+    fn krate_spanned(&self, span: proc_macro2::Span) -> TokenStream {
+        let krate = self.krate().to_token_stream().into_iter();
+        krate
+            .map(|mut tt| {
+                tt.set_span(span);
+                tt
+            })
+            .collect()
+    }
+
+    fn make_fn_block(&self, inner: &syn::Block) -> syn::Block {
+        let ok = self.ok(quote::quote!(__ret));
+        let mut block: syn::Block = syn::parse2(quote::quote! {{
+            #[allow(clippy::diverging_sub_expression)]
+            {
+                let __ret = { #inner };
+
+                #[allow(unreachable_code)]
+                #ok
+            }
+        }})
+        .unwrap();
+        block.brace_token = inner.brace_token;
+        block
+    }
+
+    // Closure bodies and async blocks are expressions, so the extra blocks used in
+    // make_fn_block would trip the unused_braces lint.
+    fn make_expr_block(&self, inner: &syn::Block) -> syn::Block {
+        let ok = self.ok(quote::quote!(__ret));
+        syn::parse2(quote::quote! {{
+            #[allow(clippy::diverging_sub_expression)]
+            let __ret = #inner;
+
+            #[allow(unreachable_code)]
+            #ok
+        }})
+        .unwrap()
+    }
+
+    // Ok wraps a value, first in `Some` with `as ResultOption`.
+    fn ok(&self, value: TokenStream) -> syn::Expr {
+        let ty = &self.return_type;
+        let krate = self.krate();
+        let value = match self.optional {
+            true => quote::quote!(::core::option::Option::Some(#value)),
+            false => value,
+        };
+        syn::parse2(quote::quote!(<#ty as #krate::__internal::_Succeed>::from_ok(#value))).unwrap()
+    }
+
+    // Ok wraps the value of a `return` from the function, closure or block.
+    fn ok_return(&mut self, i: syn::ExprReturn) -> syn::ExprReturn {
+        if let Some(expr) = &i.expr {
+            let warning = crate::warning::wrapped(&self.ok_type, &self.generics, expr);
+            self.warnings.extend(warning);
+        }
+        let ok = match &i.expr {
+            Some(expr) => self.ok(quote::quote!(#expr)),
+            None => self.ok(quote::quote!(())),
+        };
+        syn::ExprReturn {
+            expr: Some(Box::new(ok)),
+            ..i
+        }
+    }
+
+    // Ok wraps the `return`s in the block of a `try_block!`, which return from
+    // the function around it rather than exit the block, before the block is
+    // expanded on its own. Those in closures, async blocks and items inside
+    // it are left alone, as they return from those instead.
+    fn fold_try_block_returns(&mut self, tokens: TokenStream) -> TokenStream {
+        struct OkReturns<'a>(&'a mut Throws);
+        impl Fold for OkReturns<'_> {
+            fn fold_expr_return(&mut self, i: syn::ExprReturn) -> syn::ExprReturn {
+                let i = syn::fold::fold_expr_return(self, i);
+                self.0.ok_return(i)
+            }
+            fn fold_expr_closure(&mut self, i: syn::ExprClosure) -> syn::ExprClosure {
+                i
+            }
+            fn fold_expr_async(&mut self, i: syn::ExprAsync) -> syn::ExprAsync {
+                i
+            }
+            fn fold_item(&mut self, i: syn::Item) -> syn::Item {
+                i
+            }
+            fn fold_macro(&mut self, mut i: syn::Macro) -> syn::Macro {
+                if crate::crate_path::is_culpa(&i.path, "try_block", self.0.krate()) {
+                    i.tokens = self.0.fold_try_block_returns(i.tokens);
+                }
+                i
+            }
+        }
+
+        let parser = |input: syn::parse::ParseStream| {
+            let mut args = TokenStream::new();
+            let mut arrow = None;
+            if crate::args::has_arrow(input) {
+                while !input.peek(syn::Token![=>]) {
+                    args.extend(Some(input.parse::<TokenTree>()?));
+                }
+                arrow = Some(input.parse::<syn::Token![=>]>()?);
+            }
+            let block: syn::Block = input.parse()?;
+            Ok((args, arrow, block))
+        };
+        // Invalid invocations are left for the macro to report
+        match parser.parse2(tokens.clone()) {
+            Ok((args, arrow, block)) => {
+                let block = OkReturns(self).fold_block(block);
+                quote::quote!(#args #arrow #block)
+            }
+            Err(_) => tokens,
+        }
+    }
+
+    /// Transforms an async block, whose ok type is inferred.
+    pub fn fold_async(&mut self, i: syn::ExprAsync) -> syn::ExprAsync {
+        self.fold_return_type(syn::parse_quote!(-> _));
+
+        self.outer_fn = false;
+
+        let inner = self.fold_body(i.block);
+        let block = self.make_expr_block(&inner);
+
+        syn::ExprAsync { block, ..i }
+    }
+}
+
+impl Fold for Throws {
+    fn fold_item_fn(&mut self, i: syn::ItemFn) -> syn::ItemFn {
+        if !self.outer_fn {
+            return i;
+        }
+
+        let sig = self.fold_fn_output(i.sig);
+
+        self.outer_fn = false;
+
+        let block = Box::new(self.fold_fn_block(*i.block));
+
+        syn::ItemFn { sig, block, ..i }
+    }
+
+    fn fold_impl_item_fn(&mut self, i: syn::ImplItemFn) -> syn::ImplItemFn {
+        if !self.outer_fn {
+            return i;
+        }
+
+        let sig = self.fold_fn_output(i.sig);
+
+        self.outer_fn = false;
+
+        let block = self.fold_fn_block(i.block);
+
+        syn::ImplItemFn { sig, block, ..i }
+    }
+
+    fn fold_trait_item_fn(&mut self, mut i: syn::TraitItemFn) -> syn::TraitItemFn {
+        if !self.outer_fn {
+            return i;
+        }
+
+        let sig = self.fold_fn_output(i.sig);
+
+        self.outer_fn = false;
+
+        let default = i.default.take().map(|block| self.fold_fn_block(block));
+
+        syn::TraitItemFn { sig, default, ..i }
+    }
+
+    fn fold_expr_closure(&mut self, mut i: syn::ExprClosure) -> syn::ExprClosure {
+        match take_throws_attr(&mut i.attrs) {
+            Some(Ok((args, debug, krate))) => {
+                self.fold_nested_with(args, debug, krate, |throws| throws.fold_closure(i))
+            }
+            Some(Err(error)) => {
+                self.push_error(error);
+                i
+            }
+            None => i,
+        }
+    }
+
+    fn fold_expr_loop(&mut self, i: syn::ExprLoop) -> syn::ExprLoop {
+        self.loops += 1;
+        let i = syn::fold::fold_expr_loop(self, i);
+        self.loops -= 1;
+        i
+    }
+
+    fn fold_expr_while(&mut self, i: syn::ExprWhile) -> syn::ExprWhile {
+        self.loops += 1;
+        let i = syn::fold::fold_expr_while(self, i);
+        self.loops -= 1;
+        i
+    }
+
+    fn fold_expr_for_loop(&mut self, i: syn::ExprForLoop) -> syn::ExprForLoop {
+        self.loops += 1;
+        let i = syn::fold::fold_expr_for_loop(self, i);
+        self.loops -= 1;
+        i
+    }
+
+    fn fold_expr_break(&mut self, i: syn::ExprBreak) -> syn::ExprBreak {
+        if i.label.is_none() {
+            self.check_unlabeled(&i.break_token, "break");
+        }
+        syn::fold::fold_expr_break(self, i)
+    }
+
+    fn fold_expr_continue(&mut self, i: syn::ExprContinue) -> syn::ExprContinue {
+        if i.label.is_none() {
+            self.check_unlabeled(&i.continue_token, "continue");
+        }
+        i
+    }
+
+    fn fold_expr_async(&mut self, mut i: syn::ExprAsync) -> syn::ExprAsync {
+        match take_throws_attr(&mut i.attrs) {
+            // An async block has nowhere to write the return type, so it must
+            // come from the arguments
+            Some(Ok((None, _, _))) => {
+                self.push_error(syn::Error::new_spanned(
+                    i.async_token,
+                    "#[try_fn] cannot be applied to async blocks, use #[throws] instead",
+                ));
+                i
+            }
+            Some(Ok((args, debug, krate))) => {
+                self.fold_nested_with(args, debug, krate, |throws| throws.fold_async(i))
+            }
+            Some(Err(error)) => {
+                self.push_error(error);
+                i
+            }
+            None => i,
+        }
+    }
+
+    fn fold_return_type(&mut self, i: syn::ReturnType) -> syn::ReturnType {
+        if !self.outer_fn {
+            return i;
+        }
+        if let Some(errors) = self.args.as_ref().and_then(Args::errors) {
+            self.push_error(syn::Error::new_spanned(
+                errors,
+                crate::sum::FREE_FUNCTIONS_ONLY,
+            ));
+        }
+        let return_type = match &self.args {
+            Some(args) => {
+                self.ok_type = match &i {
+                    syn::ReturnType::Type(_, ty) => (**ty).clone(),
+                    syn::ReturnType::Default => syn::parse_quote!(()),
+                };
+                args.ret(i)
+            }
+            None => i,
+        };
+        let ty = match &return_type {
+            syn::ReturnType::Type(_, ty) => (**ty).clone(),
+            syn::ReturnType::Default => syn::Type::Infer(syn::parse_quote!(_)),
+        };
+        if self.args.is_none() {
+            self.ok_type = ok_type(&ty).unwrap_or_else(|| syn::parse_quote!(_));
+        }
+        let error = match &self.args {
+            Some(args) => args.error(),
+            None => error_type(&ty),
+        };
+        self.exact_errors = matches!(error, Some(ty) if contains_impl_trait(ty));
+        self.optional = matches!(&self.args, Some(args) if args.optional());
+        struct ImplTraitToInfer;
+        impl Fold for ImplTraitToInfer {
+            fn fold_type(&mut self, i: syn::Type) -> syn::Type {
+                match i {
+                    syn::Type::ImplTrait(_) => syn::Type::Infer(syn::parse_quote!(_)),
+                    i => syn::fold::fold_type(self, i),
+                }
+            }
+        }
+        self.return_type = ImplTraitToInfer.fold_type(ty);
+        return_type
+    }
+
+    fn fold_expr_return(&mut self, i: syn::ExprReturn) -> syn::ExprReturn {
+        // A return in a try block returns from the enclosing function
+        if self.try_block.is_some() {
+            return syn::fold::fold_expr_return(self, i);
+        }
+        self.ok_return(i)
+    }
+
+    fn fold_expr(&mut self, i: syn::Expr) -> syn::Expr {
+        match syn::fold::fold_expr(self, i) {
+            syn::Expr::Try(syn::ExprTry {
+                expr,
+                question_token,
+                ..
+            }) if self.rewrites_try() => {
+                let span = question_token.span;
+                let exit = match &self.try_block {
+                    Some(label) => quote::quote!(break #label),
+                    None => quote::quote!(return),
+                };
+                if self.optional {
+                    let branch = self.branch_optional(span, &expr, &exit);
+                    return syn::parse2(quote::quote!((#branch))).unwrap();
+                }
+                let thrown = self.throw_error(span);
+                let krate = self.krate_spanned(span);
+                let branch = quote::quote_spanned! {span=>
+                    match #krate::__internal::_Branch::branch(#expr) {
+                        ::core::result::Result::Ok(value) => value,
+                        ::core::result::Result::Err(error) => #exit #thrown,
+                    }
+                };
+                // Parenthesized so that it is not parsed as a statement when
+                // it starts one, as in `result? + 1`
+                syn::parse2(quote::quote!((#branch))).unwrap()
+            }
+            i => i,
+        }
+    }
+
+    fn fold_macro(&mut self, mut i: syn::Macro) -> syn::Macro {
+        let path = &i.path;
+        let throwing = ["throw", "bail", "ensure", "ensure_eq"]
+            .iter()
+            .any(|name| crate::crate_path::is_culpa(path, name, self.krate()));
+        // Nested throwing closures and blocks are their own scopes for `?`
+        let scope = ["try_block", "throws_closure", "throws_async"]
+            .iter()
+            .any(|name| crate::crate_path::is_culpa(path, name, self.krate()));
+        if crate::crate_path::is_culpa(path, "try_block", self.krate()) && self.try_block.is_none()
+        {
+            i.tokens = self.fold_try_block_returns(i.tokens);
+        } else if (throwing || is_expr_macro(path)) && self.rewrites_try() {
+            i.tokens = self.fold_macro_args(i.tokens);
+        } else if !scope && self.try_block.is_some() {
+            self.check_macro_args(&i.tokens);
+        }
+        if throwing {
+            let tokens = i.tokens;
+            i.tokens = match &self.try_block {
+                Some(label) => quote::quote!(@exit[break #label] #tokens),
+                None if self.exact_errors => quote::quote!(@exact #tokens),
+                None => tokens,
+            };
+        }
+        i
+    }
+}
+
+// Removes a `#[throws]` or `#[try_fn]` attribute, returning the arguments
+// to construct a Throws folder with if one was found, whether to debug it and
+// the path to culpa it was given.
+#[allow(clippy::type_complexity)]
+fn take_throws_attr(
+    attrs: &mut Vec<syn::Attribute>,
+) -> Option<syn::Result<(Option<Args>, Option<Span>, Option<syn::Path>)>> {
+    let index = attrs
+        .iter()
+        .position(|attr| is_attr(attr, "throws") || is_attr(attr, "try_fn"))?;
+    let attr = attrs.remove(index);
+    if is_attr(&attr, "try_fn") {
+        let args = match attr.meta {
+            syn::Meta::Path(_) => Ok(TryFnArgs::default()),
+            _ => attr.parse_args::<TryFnArgs>(),
+        };
+        return Some(args.map(|args| (None, args.debug(), args.krate().cloned())));
+    }
+    let args = match attr.meta {
+        syn::Meta::Path(_) => syn::parse2::<Args>(TokenStream::new()),
+        _ => attr.parse_args::<Args>(),
+    };
+    Some(args.map(|args| {
+        let debug = args.debug();
+        let krate = args.krate().cloned();
+        (Some(args), debug, krate)
+    }))
+}
+
+// Whether a function inside an annotated impl block, trait or module should be
+// transformed. If it opts out with `#[nothrow]`, the attribute is marked so it
+// knows it was used correctly and expands to nothing.
+fn should_fold(attrs: &mut [syn::Attribute]) -> bool {
+    if let Some(attr) = attrs.iter_mut().find(|attr| is_attr(attr, "nothrow")) {
+        let path = attr.path();
+        attr.meta = syn::parse_quote!(#path(__culpa_checked));
+        return false;
+    }
+    !attrs
+        .iter()
+        .any(|attr| is_attr(attr, "throws") || is_attr(attr, "try_fn"))
+}
+
+// The output type of an `impl Future<Output = T>` return type.
+fn future_output(output: &mut syn::ReturnType) -> Option<&mut syn::Type> {
+    let bounds = match output {
+        syn::ReturnType::Type(_, ty) => match &mut **ty {
+            syn::Type::ImplTrait(ty) => &mut ty.bounds,
+            _ => return None,
+        },
+        syn::ReturnType::Default => return None,
+    };
+    bounds.iter_mut().find_map(|bound| {
+        let segment = match bound {
+            syn::TypeParamBound::Trait(bound) => bound.path.segments.last_mut()?,
+            _ => return None,
+        };
+        if segment.ident != "Future" {
+            return None;
+        }
+        match &mut segment.arguments {
+            syn::PathArguments::AngleBracketed(args) => {
+                args.args.iter_mut().find_map(|arg| match arg {
+                    syn::GenericArgument::AssocType(assoc) if assoc.ident == "Output" => {
+                        Some(&mut assoc.ty)
+                    }
+                    _ => None,
+                })
+            }
+            _ => None,
+        }
+    })
+}
+
+// The error type of a return type written out in full, which is the last
+// argument of the innermost type taking more than one generic argument, the
+// same place Args injects the error type into. `ControlFlow` breaks with its
+// first argument instead.
+fn error_type(ty: &syn::Type) -> Option<&syn::Type> {
+    let (segment, types) = type_arguments(ty)?;
+    if segment.ident == "ControlFlow" {
+        return types.first().copied();
+    }
+    match types[..] {
+        [ty] => error_type(ty),
+        [.., last] => Some(last),
+        [] => None,
+    }
+}
+
+// The ok type of a return type written out in full, which is its first
+// generic argument, or the second one of `ControlFlow`, which defaults to `()`.
+fn ok_type(ty: &syn::Type) -> Option<syn::Type> {
+    let (segment, types) = type_arguments(ty)?;
+    if segment.ident == "ControlFlow" {
+        let ok = types.get(1).map(|ty| (*ty).clone());
+        return Some(ok.unwrap_or_else(|| syn::parse_quote!(())));
+    }
+    types.first().map(|ty| (*ty).clone())
+}
+
+// The last segment of a path type and its generic type arguments.
+fn type_arguments(ty: &syn::Type) -> Option<(&syn::PathSegment, Vec<&syn::Type>)> {
+    let segment = match ty {
+        syn::Type::Path(ty) => ty.path.segments.last()?,
+        _ => return None,
+    };
+    let types = match &segment.arguments {
+        syn::PathArguments::AngleBracketed(args) => args.args.iter().filter_map(|arg| match arg {
+            syn::GenericArgument::Type(ty) => Some(ty),
+            _ => None,
+        }),
+        _ => return None,
+    };
+    Some((segment, types.collect()))
+}
+
+fn contains_impl_trait(ty: &syn::Type) -> bool {
+    struct Finder(bool);
+    impl<'ast> syn::visit::Visit<'ast> for Finder {
+        fn visit_type_impl_trait(&mut self, _: &'ast syn::TypeImplTrait) {
+            self.0 = true;
+        }
+    }
+    let mut finder = Finder(false);
+    syn::visit::Visit::visit_type(&mut finder, ty);
+    finder.0
+}
+
+// Whether a macro is one of the standard macros taking expressions, whose
+// arguments can be rewritten.
+fn is_expr_macro(path: &syn::Path) -> bool {
+    const EXPR_MACROS: &[&str] = &[
+        "assert",
+        "assert_eq",
+        "assert_ne",
+        "dbg",
+        "debug_assert",
+        "debug_assert_eq",
+        "debug_assert_ne",
+        "eprint",
+        "eprintln",
+        "format",
+        "format_args",
+        "panic",
+        "print",
+        "println",
+        "todo",
+        "unimplemented",
+        "unreachable",
+        "vec",
+        "write",
+        "writeln",
+    ];
+    let std = match path.segments.len() {
+        1 => path.leading_colon.is_none(),
+        2 => ["std", "core", "alloc"]
+            .iter()
+            .any(|krate| path.segments[0].ident == krate),
+        _ => false,
+    };
+    std && EXPR_MACROS
+        .iter()
+        .any(|name| path.segments.last().unwrap().ident == name)
+}
+
+// Whether some tokens contain a `?` operator.
+fn contains_try(tokens: TokenStream) -> bool {
+    find_try(tokens).is_some()
+}
+
+// The span of the first `?` operator in some tokens, which is a `?` after an
+// operand, unlike the `?` of a bound like `?Sized`.
+fn find_try(tokens: TokenStream) -> Option<Span> {
+    let mut operand = false;
+    for tt in tokens {
+        match tt {
+            TokenTree::Punct(punct) if punct.as_char() == '?' && operand => {
+                return Some(punct.span());
+            }
+            TokenTree::Punct(punct) => operand = punct.as_char() == '?',
+            TokenTree::Group(group) => {
+                if let Some(span) = find_try(group.stream()) {
+                    return Some(span);
+                }
+                operand = true;
+            }
+            TokenTree::Ident(_) | TokenTree::Literal(_) => operand = true,
+        }
+    }
+    None
+}
+
+fn is_attr(attr: &syn::Attribute, name: &str) -> bool {
+    attr.path().segments.last().unwrap().ident == name
+}
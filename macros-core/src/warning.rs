@@ -0,0 +1,113 @@
+// This module implements the warnings for explicitly wrapped return values.
+//
+// The body of a throwing function is ok-wrapped, so a tail or return
+// expression like `Ok(x)` becomes `Ok(Ok(x))`, which is rarely intended and
+// otherwise shows up as a confusing type error. The same goes for `Some(x)`
+// in a function returning an `Option`, and `Err(error)`, which should be
+// thrown instead.
+//
+// Proc macros cannot emit warnings on stable, so each warning is the use of a
+// deprecated constant, spanned to the wrapper and with the suggestion as its
+// deprecation note. No warning is emitted when the ok type could itself be
+// the wrapper, because it is a `Result` or `Option`, is inferred, or is a type
+// parameter of the function or of its impl block or trait.
+
+use proc_macro2::TokenStream;
+
+// Warns about the explicitly wrapped values a block evaluates to, looking
+// through the branches of any `if` or `match` it ends with.
+pub fn tail(
+    ok_type: &syn::Type,
+    generics: &[syn::Ident],
+    block: &syn::Block,
+    warnings: &mut Vec<TokenStream>,
+) {
+    if let Some(syn::Stmt::Expr(expr, None)) = block.stmts.last() {
+        tail_expr(ok_type, generics, expr, warnings);
+    }
+}
+
+fn tail_expr(
+    ok_type: &syn::Type,
+    generics: &[syn::Ident],
+    expr: &syn::Expr,
+    warnings: &mut Vec<TokenStream>,
+) {
+    match expr {
+        syn::Expr::Block(expr) => tail(ok_type, generics, &expr.block, warnings),
+        syn::Expr::Unsafe(expr) => tail(ok_type, generics, &expr.block, warnings),
+        syn::Expr::Paren(expr) => tail_expr(ok_type, generics, &expr.expr, warnings),
+        syn::Expr::If(expr) => {
+            tail(ok_type, generics, &expr.then_branch, warnings);
+            if let Some((_, else_branch)) = &expr.else_branch {
+                tail_expr(ok_type, generics, else_branch, warnings);
+            }
+        }
+        syn::Expr::Match(expr) => {
+            for arm in &expr.arms {
+                tail_expr(ok_type, generics, &arm.body, warnings);
+            }
+        }
+        expr => warnings.extend(wrapped(ok_type, generics, expr)),
+    }
+}
+
+// Warns if a returned value is explicitly wrapped.
+pub fn wrapped(
+    ok_type: &syn::Type,
+    generics: &[syn::Ident],
+    expr: &syn::Expr,
+) -> Option<TokenStream> {
+    let call = match expr {
+        syn::Expr::Call(call) if call.args.len() == 1 => call,
+        _ => return None,
+    };
+    let wrapper = match &*call.func {
+        syn::Expr::Path(path) if path.qself.is_none() => &path.path.segments.last()?.ident,
+        _ => return None,
+    };
+    let note = if wrapper == "Ok" || wrapper == "Some" {
+        format!(
+            "the returned value is wrapped in `{0}` already, write `x` instead of `{0}(x)`",
+            wrapper
+        )
+    } else if wrapper == "Err" {
+        "errors must be thrown, write `throw!(error)` instead of `Err(error)`".to_string()
+    } else {
+        return None;
+    };
+    if may_be_wrapper(ok_type, generics, wrapper) {
+        return None;
+    }
+    let constant = syn::Ident::new("explicitly_wrapped", wrapper.span());
+    Some(quote::quote! {
+        let _ = {
+            #[deprecated(note = #note)]
+            #[allow(non_upper_case_globals)]
+            const explicitly_wrapped: () = ();
+            #constant
+        };
+    })
+}
+
+// Whether the ok type could be the type of the wrapper, so that wrapping the
+// returned value is intended.
+fn may_be_wrapper(ok_type: &syn::Type, generics: &[syn::Ident], wrapper: &syn::Ident) -> bool {
+    if let syn::Type::Path(ty) = ok_type {
+        // A type parameter could be anything
+        if ty.qself.is_none() && generics.iter().any(|param| ty.path.is_ident(param)) {
+            return true;
+        }
+    }
+    match ok_type {
+        syn::Type::Path(ty) => match ty.path.segments.last() {
+            Some(segment) if segment.ident == "Result" => wrapper != "Some",
+            Some(segment) if segment.ident == "Option" => wrapper == "Some",
+            _ => false,
+        },
+        syn::Type::Paren(ty) => may_be_wrapper(&ty.elem, generics, wrapper),
+        syn::Type::Group(ty) => may_be_wrapper(&ty.elem, generics, wrapper),
+        syn::Type::Infer(_) | syn::Type::ImplTrait(_) | syn::Type::Macro(_) => true,
+        _ => false,
+    }
+}
@@ -0,0 +1,52 @@
+use std::env;
+use std::fs;
+
+use quote::quote;
+
+use culpa_macros_core as macros;
+
+// In its own test binary, since the environment is shared by the whole process
+#[test]
+fn renamed_dependency() {
+    let dir = env::temp_dir().join(format!("culpa-crate-path-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(
+        dir.join("Cargo.toml"),
+        "[package]\n\
+         name = \"facade\"\n\
+         version = \"0.1.0\"\n\
+         \n\
+         [dependencies]\n\
+         errors = { package = \"culpa\", version = \"1\" }\n",
+    )
+    .unwrap();
+    env::set_var("CARGO_MANIFEST_DIR", &dir);
+    env::remove_var("CARGO");
+
+    let output = macros::throws(
+        quote!(Error),
+        quote!(
+            fn answer() -> u8 {
+                42
+            }
+        ),
+    )
+    .unwrap();
+    let explicit = macros::throws(
+        quote!(Error, crate = facade::culpa),
+        quote!(
+            fn answer() -> u8 {
+                42
+            }
+        ),
+    )
+    .unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+
+    assert!(output
+        .to_string()
+        .contains(":: errors :: __internal :: _Succeed"));
+    assert!(explicit
+        .to_string()
+        .contains("facade :: culpa :: __internal :: _Succeed"));
+}
@@ -0,0 +1,92 @@
+use culpa_macros_core::expand;
+
+#[test]
+fn function() {
+    let source = r#"
+#[throws(Error)]
+fn answer() -> u8 {
+    if false {
+        throw!(Error);
+    }
+    42
+}
+"#;
+    let expanded = r#"fn answer() -> ::core::result::Result<u8, Error> {
+    #[allow(clippy::diverging_sub_expression)]
+    {
+        let __ret = {
+            {
+                if false {
+                    throw!(Error);
+                }
+                42
+            }
+        };
+        #[allow(unreachable_code)]
+        <::core::result::Result<
+            u8,
+            Error,
+        > as ::culpa::__internal::_Succeed>::from_ok(__ret)
+    }
+}
+"#;
+    assert_eq!(expand(source), expanded);
+}
+
+#[test]
+fn method() {
+    let source = r#"
+struct Parser;
+
+impl Parser {
+    #[culpa::try_fn]
+    fn length(&self, input: &str) -> Result<usize, Error> {
+        input.len()
+    }
+
+    fn new() -> Parser {
+        Parser
+    }
+}
+"#;
+    let expanded = r#"struct Parser;
+impl Parser {
+    fn length(&self, input: &str) -> Result<usize, Error> {
+        #[allow(clippy::diverging_sub_expression)]
+        {
+            let __ret = { { input.len() } };
+            #[allow(unreachable_code)]
+            <Result<usize, Error> as ::culpa::__internal::_Succeed>::from_ok(__ret)
+        }
+    }
+    fn new() -> Parser {
+        Parser
+    }
+}
+"#;
+    assert_eq!(expand(source), expanded);
+}
+
+#[test]
+fn error() {
+    let source = r#"
+#[throws(Error as Wrapper, u8)]
+fn stray() {}
+"#;
+    let expanded = r#"::core::compile_error! {
+    "unexpected argument, expected `Error`, `as Wrapper` or `Error as Wrapper`"
+}
+"#;
+    assert_eq!(expand(source), expanded);
+}
+
+#[test]
+fn parse_error() {
+    // The message comes from the lexer, and changes between versions of syn
+    let expanded = expand("fn unclosed() {");
+    assert!(
+        expanded.starts_with("::core::compile_error! {"),
+        "{}",
+        expanded
+    );
+}
@@ -0,0 +1,502 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+
+use culpa_macros_core as macros;
+
+// Formats the output of a macro so that the snapshots are readable, as an
+// expression in a function if it is not made of items.
+fn pretty(output: syn::Result<TokenStream>) -> String {
+    let output = output.unwrap_or_else(syn::Error::into_compile_error);
+    let file = match syn::parse2::<syn::File>(output.clone()) {
+        Ok(file) => file,
+        Err(_) => syn::parse_quote!(fn expr() { #output }),
+    };
+    prettyplease::unparse(&file)
+}
+
+#[test]
+fn throws() {
+    insta::assert_snapshot!(pretty(macros::throws(
+        quote!(io::Error),
+        quote! {
+            fn read(path: &str) -> String {
+                if path.is_empty() {
+                    throw!(io::Error::from(io::ErrorKind::NotFound));
+                }
+                if path == "-" {
+                    return String::new();
+                }
+                fs::read_to_string(path).unwrap()
+            }
+        },
+    )));
+}
+
+#[test]
+fn throws_default_error() {
+    insta::assert_snapshot!(pretty(macros::throws(
+        quote!(),
+        quote! {
+            fn check(value: u8) {
+                ensure!(value > 0, Error::Zero);
+            }
+        },
+    )));
+}
+
+#[test]
+fn throws_as_option() {
+    insta::assert_snapshot!(pretty(macros::throws(
+        quote!(as Option),
+        quote! {
+            fn first(values: &[u8]) -> u8 {
+                if values.is_empty() {
+                    throw!();
+                }
+                values[0]
+            }
+        },
+    )));
+}
+
+#[test]
+fn throws_as_poll() {
+    insta::assert_snapshot!(pretty(macros::throws(
+        quote!(io::Error as Poll),
+        quote! {
+            fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> usize {
+                Poll::Ready(0)
+            }
+        },
+    )));
+}
+
+#[test]
+fn throws_as_control_flow() {
+    insta::assert_snapshot!(pretty(macros::throws(
+        quote!(i32 as ControlFlow),
+        quote! {
+            fn visit(value: i32) -> u32 {
+                if value < 0 {
+                    throw!(value);
+                }
+                1
+            }
+        },
+    )));
+}
+
+#[cfg(not(feature = "trace"))]
+#[test]
+fn throws_as_result_option() {
+    insta::assert_snapshot!(pretty(macros::throws(
+        quote!(io::Error as ResultOption),
+        quote! {
+            fn lookup(key: &str) -> u8 {
+                if key.is_empty() {
+                    throw!();
+                }
+                find(key)?
+            }
+        },
+    )));
+}
+
+#[test]
+fn throws_placeholders() {
+    insta::assert_snapshot!(pretty(macros::throws(
+        quote!(io::Error as Reply<'static, @ok, Context, @err>),
+        quote! {
+            fn answer() -> u8 {
+                42
+            }
+        },
+    )));
+}
+
+#[test]
+fn throws_impl_trait_error() {
+    insta::assert_snapshot!(pretty(macros::throws(
+        quote!(impl Debug),
+        quote! {
+            fn parse(input: &str) -> u8 {
+                if input.is_empty() {
+                    throw!(Empty);
+                }
+                input.parse()?
+            }
+        },
+    )));
+}
+
+// Type parameters could be the wrappers, so explicitly wrapping a value
+// returned as one is not warned about
+#[test]
+fn throws_generic_ok_type() {
+    insta::assert_snapshot!(pretty(macros::throws(
+        quote!(Error),
+        quote! {
+            impl<T> Cache<T> {
+                fn get(&self) -> T {
+                    Ok(self.value())
+                }
+
+                fn convert<U>(&self) -> U {
+                    Some(self.value())
+                }
+            }
+        },
+    )));
+}
+
+#[test]
+fn throws_impl_future() {
+    insta::assert_snapshot!(pretty(macros::throws(
+        quote!(io::Error),
+        quote! {
+            fn load(&self, path: String) -> impl Future<Output = String> {
+                async move { read(path).await }
+            }
+        },
+    )));
+}
+
+#[test]
+fn throws_impl_block() {
+    insta::assert_snapshot!(pretty(macros::throws(
+        quote!(StoreError),
+        quote! {
+            impl Store {
+                fn get(&self, key: &str) -> u8 {
+                    self.0
+                }
+
+                #[nothrow]
+                fn len(&self) -> usize {
+                    1
+                }
+            }
+        },
+    )));
+}
+
+#[test]
+fn throws_several_errors() {
+    insta::assert_snapshot!(pretty(macros::throws(
+        quote!(io::Error | ParseIntError),
+        quote! {
+            fn read_number(path: &str) -> u8 {
+                throw!(Missing)
+            }
+        },
+    )));
+}
+
+#[test]
+fn throws_closure_attribute() {
+    insta::assert_snapshot!(pretty(macros::throws(
+        quote!(io::Error),
+        quote! {
+            fn read_all(paths: &[&str]) {
+                let read = #[throws(io::Error)] |path: &str| -> String { read(path) };
+                paths.iter().try_for_each(#[try_fn] |path| -> io::Result<()> {})
+            }
+        },
+    )));
+}
+
+#[test]
+fn throws_crate_path() {
+    insta::assert_snapshot!(pretty(macros::throws(
+        quote!(io::Error, crate = errors::culpa),
+        quote! {
+            fn read(paths: &[&str]) -> usize {
+                let count = #[try_fn] || -> Result<usize, Error> { paths.len() };
+                count().unwrap()
+            }
+        },
+    )));
+}
+
+#[test]
+fn try_fn_crate_path() {
+    insta::assert_snapshot!(pretty(macros::try_fn(
+        quote!(crate = errors::culpa),
+        quote! {
+            fn answer() -> Result<u8, Error> {
+                42
+            }
+        },
+    )));
+}
+
+#[test]
+fn try_fn() {
+    insta::assert_snapshot!(pretty(macros::try_fn(
+        quote!(),
+        quote! {
+            fn parse(input: &str) -> Result<u8, Error> {
+                if input.is_empty() {
+                    throw!(Error::Empty);
+                }
+                42
+            }
+        },
+    )));
+}
+
+#[test]
+fn throws_closure() {
+    insta::assert_snapshot!(pretty(macros::throws_closure(quote! {
+        io::Error => |path: &str| read(path)
+    })));
+}
+
+#[test]
+fn throws_async() {
+    insta::assert_snapshot!(pretty(macros::throws_async(quote! {
+        io::Error => async move { read(path).await }
+    })));
+}
+
+#[test]
+fn try_block() {
+    insta::assert_snapshot!(pretty(macros::try_block(quote! {
+        Option => {
+            if values.is_empty() {
+                throw!();
+            }
+            values[0]
+        }
+    })));
+}
+
+#[test]
+fn try_block_option_debug() {
+    insta::assert_snapshot!(pretty(macros::try_block(quote! {
+        Option, debug => { 1 }
+    })));
+}
+
+#[cfg(not(feature = "trace"))]
+#[test]
+fn question_mark_in_try_block() {
+    insta::assert_snapshot!(pretty(macros::try_block(quote! {
+        ParseIntError => {
+            input.parse::<u8>()? + 1
+        }
+    })));
+}
+
+#[cfg(feature = "trace")]
+#[test]
+fn question_mark_in_try_block_traced() {
+    insta::assert_snapshot!(pretty(macros::try_block(quote! {
+        ParseIntError => {
+            input.parse::<u8>()? + 1
+        }
+    })));
+}
+
+#[cfg(feature = "trace")]
+#[test]
+fn question_mark_traced() {
+    insta::assert_snapshot!(pretty(macros::throws(
+        quote!(Traced<NotFound>),
+        quote! {
+            fn lookup() -> u32 {
+                find("key")? + 1
+            }
+        },
+    )));
+}
+
+#[test]
+fn main() {
+    insta::assert_snapshot!(pretty(macros::main(
+        quote!(io::Error, exit_code = 2),
+        quote! {
+            fn main() {
+                run();
+            }
+        },
+    )));
+}
+
+#[test]
+fn main_missing_comma() {
+    insta::assert_snapshot!(pretty(macros::main(
+        quote!(io::Error exit_code = 2),
+        quote!(
+            fn main() {}
+        ),
+    )));
+}
+
+#[test]
+fn main_success_exit_code() {
+    insta::assert_snapshot!(pretty(macros::main(
+        quote!(io::Error, exit_code = 0),
+        quote!(
+            fn main() {}
+        ),
+    )));
+}
+
+#[test]
+fn main_arguments() {
+    insta::assert_snapshot!(pretty(macros::main(
+        quote!(),
+        quote!(
+            fn main(args: Vec<String>) {}
+        ),
+    )));
+}
+
+#[test]
+fn main_crate_path() {
+    insta::assert_snapshot!(pretty(macros::main(
+        quote!(crate = errors::culpa, exit_code = 2),
+        quote! {
+            fn main() {
+                run();
+            }
+        },
+    )));
+}
+
+#[test]
+fn config() {
+    insta::assert_snapshot!(pretty(macros::config(
+        quote!(error = ParseError, crate = errors::culpa),
+        quote! {
+            mod parsing {
+                use errors::culpa::{throws, throws_closure, try_fn};
+
+                #[throws]
+                fn parse(input: &str) -> u8 {
+                    parse_u8(input)
+                }
+
+                #[throws(_ as Poll, debug)]
+                fn poll() -> u8 {
+                    Poll::Pending
+                }
+
+                #[try_fn]
+                fn checked() -> Result<(), io::Error> {
+                    let closure = throws_closure!(|input: &str| parse(input));
+                }
+            }
+        },
+    )));
+}
+
+#[test]
+fn config_paths() {
+    insta::assert_snapshot!(pretty(macros::config(
+        quote!(error = ParseError, crate = errors::culpa),
+        quote! {
+            mod parsing {
+                #[culpa::main(exit_code = 2)]
+                fn main() {}
+
+                #[errors::culpa::throws]
+                fn parse(input: &str) -> u8 {
+                    parse_u8(input)
+                }
+
+                #[other::throws]
+                fn other() {}
+
+                fn sum() -> Result<u8, ParseError> {
+                    other::try_block!({ 1 })
+                }
+
+                fn first() -> Option<u8> {
+                    use culpa::try_block;
+
+                    try_block!(Option => { 1 })
+                }
+            }
+        },
+    )));
+}
+
+#[test]
+fn config_imports() {
+    insta::assert_snapshot!(pretty(macros::config(
+        quote!(error = ParseError, crate = errors::culpa),
+        quote! {
+            mod parsing {
+                use culpa::*;
+                use other::throws;
+
+                #[throws]
+                fn other() {}
+
+                #[try_fn]
+                fn checked() -> Result<(), ParseError> {}
+
+                mod nested {
+                    use culpa::try_fn as fallible;
+
+                    #[try_fn]
+                    fn unimported() -> Result<(), ParseError> {}
+
+                    #[fallible]
+                    fn renamed() -> Result<(), ParseError> {}
+                }
+            }
+        },
+    )));
+}
+
+#[test]
+fn derive_fallible() {
+    insta::assert_snapshot!(pretty(macros::derive_fallible(quote! {
+        enum Outcome<T> {
+            #[culpa(ok)]
+            Done(T),
+            #[culpa(err)]
+            Failed(String),
+        }
+    })));
+}
+
+#[test]
+fn derive_fallible_crate_path() {
+    insta::assert_snapshot!(pretty(macros::derive_fallible(quote! {
+        #[culpa(crate = errors::culpa)]
+        enum Status {
+            #[culpa(ok)]
+            Running,
+            #[culpa(err)]
+            Stopped(i32),
+        }
+    })));
+}
+
+#[test]
+fn debug() {
+    insta::assert_snapshot!(pretty(macros::throws(
+        quote!(Error, debug),
+        quote! {
+            fn answer() -> u8 {
+                42
+            }
+        },
+    )));
+}
+
+#[test]
+fn nothrow_outside_throws() {
+    insta::assert_snapshot!(pretty(macros::nothrow(
+        quote!(),
+        quote! {
+            fn len(&self) -> usize {
+                1
+            }
+        },
+    )));
+}
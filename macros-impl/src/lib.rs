@@ -0,0 +1,17 @@
+//! Shows what the `#[throws]` and `#[try_fn]` attributes in some source code expand to.
+//!
+//! The transformations behind the culpa macros live in `culpa-macros-core`, and this crate only
+//! re-exports its [`expand`] function, so that tools calling `culpa_macros_impl::expand` keep
+//! working.
+//!
+//! ```
+//! let expanded = culpa_macros_impl::expand(
+//!     "#[throws(Error)]
+//!     fn answer() -> u8 {
+//!         42
+//!     }",
+//! );
+//! assert!(expanded.contains("fn answer() -> ::core::result::Result<u8, Error> {"));
+//! ```
+
+pub use culpa_macros_core::expand;
@@ -1,21 +1,63 @@
 extern crate proc_macro;
 
-mod args;
-mod throws;
-
 use proc_macro::TokenStream;
 
-use args::Args;
-use throws::Throws;
-
 #[proc_macro_attribute]
 pub fn throws(args: TokenStream, input: TokenStream) -> TokenStream {
-    let args = syn::parse_macro_input!(args as Args);
-    Throws::new(Some(args)).fold(input)
+    output(culpa_macros_core::throws(args.into(), input.into()))
 }
 
 #[proc_macro_attribute]
 pub fn try_fn(args: TokenStream, input: TokenStream) -> TokenStream {
-    assert!(args.to_string() == "", "try_fn does not take arguments");
-    Throws::new(None).fold(input)
+    output(culpa_macros_core::try_fn(args.into(), input.into()))
+}
+
+#[proc_macro_attribute]
+pub fn nothrow(args: TokenStream, input: TokenStream) -> TokenStream {
+    output(culpa_macros_core::nothrow(args.into(), input.into()))
+}
+
+#[proc_macro]
+pub fn throws_closure(input: TokenStream) -> TokenStream {
+    expr_output(culpa_macros_core::throws_closure(input.into()))
+}
+
+#[proc_macro]
+pub fn throws_async(input: TokenStream) -> TokenStream {
+    expr_output(culpa_macros_core::throws_async(input.into()))
+}
+
+#[proc_macro]
+pub fn try_block(input: TokenStream) -> TokenStream {
+    expr_output(culpa_macros_core::try_block(input.into()))
+}
+
+#[proc_macro_attribute]
+pub fn main(args: TokenStream, input: TokenStream) -> TokenStream {
+    output(culpa_macros_core::main(args.into(), input.into()))
+}
+
+#[proc_macro_attribute]
+pub fn config(args: TokenStream, input: TokenStream) -> TokenStream {
+    output(culpa_macros_core::config(args.into(), input.into()))
+}
+
+#[proc_macro_derive(Fallible, attributes(culpa))]
+pub fn derive_fallible(input: TokenStream) -> TokenStream {
+    output(culpa_macros_core::derive_fallible(input.into()))
+}
+
+fn output(result: syn::Result<proc_macro2::TokenStream>) -> TokenStream {
+    result.unwrap_or_else(syn::Error::into_compile_error).into()
+}
+
+// The errors of a macro used as an expression are reported in a block, as
+// there can be more than one.
+fn expr_output(result: syn::Result<proc_macro2::TokenStream>) -> TokenStream {
+    let errors = |error: syn::Error| {
+        let errors = error.into_compile_error();
+        let block = proc_macro2::Group::new(proc_macro2::Delimiter::Brace, errors);
+        proc_macro2::TokenTree::from(block).into()
+    };
+    result.unwrap_or_else(errors).into()
 }
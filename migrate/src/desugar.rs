@@ -0,0 +1,309 @@
+// Rewrites a function using `#[throws]` or `#[try_fn]` to return a `Result`
+// or `Option` explicitly.
+//
+// The values the function returns are wrapped in `Ok` or `Some`, and errors
+// thrown with `throw!(error)` are returned with `Err(error)?`, which converts
+// them the same way. `throw!` with a context or message is left alone, since
+// it can be used outside of culpa functions too. Only culpa's own `throw!` and
+// `bail!` are rewritten: those named through `culpa::`, or named alone when
+// the file imports them from culpa and not from anywhere else, so that macros
+// like `anyhow::bail!` are left alone.
+//
+// Closures and async blocks annotated with `#[throws]` or `#[try_fn]` only
+// work inside a function that is itself annotated, so functions containing
+// them are skipped.
+
+use syn::parse::{Parse, ParseStream};
+use syn::spanned::Spanned;
+use syn::visit::Visit;
+
+use crate::edit::{Edits, Source};
+use crate::tail::{self, Leaf};
+use crate::{Function, Kind};
+
+const UNSUPPORTED_RETURN_TYPE: &str = "only functions returning `Result` or `Option` are supported";
+
+pub fn desugar(
+    source: &Source,
+    function: &Function,
+    imports: &[syn::Ident],
+) -> Option<Result<Edits, String>> {
+    let index = crate::culpa_attr(function.attrs)?;
+    let attr = &function.attrs[index];
+    let sig = function.sig;
+    if sig.asyncness.is_none() && returns_impl_future(&sig.output) {
+        return Some(Err(
+            "functions returning `impl Future` are not supported".to_string()
+        ));
+    }
+
+    let mut edits = Edits::default();
+    let kind = if crate::is_attr(attr, "throws") {
+        let args = match &attr.meta {
+            syn::Meta::Path(_) => ThrowsArgs::default(),
+            _ => match attr.parse_args() {
+                Ok(args) => args,
+                Err(_) => return Some(Err(
+                    "only an error type and `as Option` are supported as arguments of #[throws]"
+                        .to_string(),
+                )),
+            },
+        };
+        let ok = match &sig.output {
+            syn::ReturnType::Type(_, ty) => source.text(ty.span()),
+            syn::ReturnType::Default => "()",
+        };
+        let (kind, ty) = match &args.wrapper {
+            None => {
+                let error = match &args.error {
+                    Some(error) => source.text(error.span()),
+                    None => "Error",
+                };
+                (Kind::Result, format!("Result<{}, {}>", ok, error))
+            }
+            Some(wrapper) if wrapper.is_ident("Option") => {
+                (Kind::Option, format!("Option<{}>", ok))
+            }
+            Some(_) => return Some(Err(UNSUPPORTED_RETURN_TYPE.to_string())),
+        };
+        match &sig.output {
+            syn::ReturnType::Type(_, output) => edits.replace(source.range(output.span()), ty),
+            syn::ReturnType::Default => {
+                let params = source.end(sig.paren_token.span.close());
+                edits.insert(params, format!(" -> {}", ty));
+            }
+        }
+        kind
+    } else {
+        match &sig.output {
+            syn::ReturnType::Type(_, ty) => match crate::returned(ty) {
+                Some((kind, _, _)) => kind,
+                None => return Some(Err(UNSUPPORTED_RETURN_TYPE.to_string())),
+            },
+            syn::ReturnType::Default => return Some(Err(UNSUPPORTED_RETURN_TYPE.to_string())),
+        }
+    };
+    let end = source.skip_whitespace(source.end(attr.span()));
+    edits.delete(source.start(attr.span())..end);
+
+    let mut body = Body {
+        source,
+        kind,
+        edits,
+        imports,
+        annotated: false,
+    };
+    for leaf in tail::leaves(function.block) {
+        body.leaf(leaf);
+    }
+    body.visit_block(function.block);
+    if body.annotated {
+        return Some(Err(
+            "it contains a closure or async block annotated with #[throws] or #[try_fn]"
+                .to_string(),
+        ));
+    }
+    Some(Ok(body.edits))
+}
+
+struct Body<'a> {
+    source: &'a Source<'a>,
+    kind: Kind,
+    edits: Edits,
+    imports: &'a [syn::Ident],
+    annotated: bool,
+}
+
+impl Body<'_> {
+    fn leaf(&mut self, leaf: Leaf) {
+        match leaf {
+            Leaf::Tail(expr) | Leaf::Arm(expr) => {
+                if !crate::diverges(expr) {
+                    self.wrap(expr);
+                }
+            }
+            Leaf::Empty(block) => {
+                let ok = format!("{}(())", self.kind.ok());
+                match block.stmts.last() {
+                    Some(stmt) if diverges(stmt) => {}
+                    Some(stmt) => {
+                        let end = self.source.end(stmt.span());
+                        let indent = self.source.indent(self.source.start(stmt.span()));
+                        self.edits.insert(end, format!("\n{}{}", indent, ok));
+                    }
+                    None => {
+                        let start = self.source.end(block.brace_token.span.open());
+                        self.edits.insert(start, format!(" {} ", ok));
+                    }
+                }
+            }
+        }
+    }
+
+    fn wrap(&mut self, expr: &syn::Expr) {
+        let range = self.source.range(expr.span());
+        self.edits
+            .insert(range.start, format!("{}(", self.kind.ok()));
+        self.edits.insert(range.end, ")");
+    }
+}
+
+impl<'ast> Visit<'ast> for Body<'_> {
+    // Closures, async blocks and nested items return their own values
+
+    fn visit_expr_closure(&mut self, i: &'ast syn::ExprClosure) {
+        self.annotated |= crate::culpa_attr(&i.attrs).is_some();
+    }
+
+    fn visit_expr_async(&mut self, i: &'ast syn::ExprAsync) {
+        self.annotated |= crate::culpa_attr(&i.attrs).is_some();
+    }
+
+    fn visit_item(&mut self, _: &'ast syn::Item) {}
+
+    fn visit_expr_return(&mut self, i: &'ast syn::ExprReturn) {
+        match &i.expr {
+            Some(expr) => self.wrap(expr),
+            None => {
+                let end = self.source.end(i.return_token.span);
+                self.edits.insert(end, format!(" {}(())", self.kind.ok()));
+            }
+        }
+        syn::visit::visit_expr_return(self, i);
+    }
+
+    fn visit_macro(&mut self, i: &'ast syn::Macro) {
+        if !is_throw(&i.path, self.imports) {
+            return;
+        }
+        let range = self.source.range(i.span());
+        match self.kind {
+            Kind::Option => self.edits.replace(range, "return None"),
+            Kind::Result if i.tokens.is_empty() => {}
+            Kind::Result => {
+                if let Ok(error) = syn::parse2::<syn::Expr>(i.tokens.clone()) {
+                    let error = self.source.text(error.span());
+                    self.edits.replace(range, format!("Err({})?", error));
+                }
+            }
+        }
+    }
+}
+
+// The arguments of #[throws] that can be desugared, an error type and a
+// wrapper type
+#[derive(Default)]
+struct ThrowsArgs {
+    error: Option<syn::Type>,
+    wrapper: Option<syn::Path>,
+}
+
+impl Parse for ThrowsArgs {
+    fn parse(input: ParseStream) -> syn::Result<ThrowsArgs> {
+        let error = if input.is_empty() || input.peek(syn::Token![as]) {
+            None
+        } else {
+            match input.parse()? {
+                syn::Type::Infer(_) => None,
+                error => Some(error),
+            }
+        };
+        let wrapper = if input.parse::<Option<syn::Token![as]>>()?.is_some() {
+            Some(input.parse()?)
+        } else {
+            None
+        };
+        Ok(ThrowsArgs { error, wrapper })
+    }
+}
+
+// The names `throw!` and `bail!` are imported from culpa under in a file,
+// leaving out those also imported from elsewhere.
+pub fn imports(file: &syn::File) -> Vec<syn::Ident> {
+    let mut imports = Imports::default();
+    imports.visit_file(file);
+    let Imports { culpa, others } = imports;
+    culpa
+        .into_iter()
+        .filter(|name| !others.contains(name))
+        .collect()
+}
+
+#[derive(Default)]
+struct Imports {
+    culpa: Vec<syn::Ident>,
+    others: Vec<syn::Ident>,
+}
+
+impl Imports {
+    fn tree(&mut self, prefix: &mut Vec<syn::Ident>, tree: &syn::UseTree) {
+        let culpa = matches!(&prefix[..], [culpa] if culpa == "culpa");
+        let throw = |name: &syn::Ident| name == "throw" || name == "bail";
+        match tree {
+            syn::UseTree::Path(tree) => {
+                prefix.push(tree.ident.clone());
+                self.tree(prefix, &tree.tree);
+                prefix.pop();
+            }
+            syn::UseTree::Name(tree) if culpa && throw(&tree.ident) => {
+                self.culpa.push(tree.ident.clone());
+            }
+            syn::UseTree::Rename(tree) if culpa && throw(&tree.ident) => {
+                self.culpa.push(tree.rename.clone());
+            }
+            syn::UseTree::Name(tree) => self.others.push(tree.ident.clone()),
+            syn::UseTree::Rename(tree) => self.others.push(tree.rename.clone()),
+            syn::UseTree::Glob(tree) if culpa => {
+                for name in ["throw", "bail"] {
+                    self.culpa.push(syn::Ident::new(name, tree.star_token.span));
+                }
+            }
+            syn::UseTree::Glob(_) => {}
+            syn::UseTree::Group(group) => {
+                for tree in &group.items {
+                    self.tree(prefix, tree);
+                }
+            }
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for Imports {
+    fn visit_item_use(&mut self, i: &'ast syn::ItemUse) {
+        self.tree(&mut Vec::new(), &i.tree);
+    }
+}
+
+// Whether a macro is culpa's `throw!` or `bail!`.
+fn is_throw(path: &syn::Path, imports: &[syn::Ident]) -> bool {
+    let segments: Vec<&syn::Ident> = path.segments.iter().map(|segment| &segment.ident).collect();
+    match &segments[..] {
+        [culpa, name] => *culpa == "culpa" && (*name == "throw" || *name == "bail"),
+        [name] => path.leading_colon.is_none() && imports.contains(name),
+        _ => false,
+    }
+}
+
+fn diverges(stmt: &syn::Stmt) -> bool {
+    match stmt {
+        syn::Stmt::Expr(expr, _) => crate::diverges(expr),
+        syn::Stmt::Macro(stmt) => crate::diverging_macro(&stmt.mac),
+        _ => false,
+    }
+}
+
+fn returns_impl_future(output: &syn::ReturnType) -> bool {
+    let bounds = match output {
+        syn::ReturnType::Type(_, ty) => match &**ty {
+            syn::Type::ImplTrait(ty) => &ty.bounds,
+            _ => return false,
+        },
+        syn::ReturnType::Default => return false,
+    };
+    bounds.iter().any(|bound| match bound {
+        syn::TypeParamBound::Trait(bound) => {
+            matches!(bound.path.segments.last(), Some(segment) if segment.ident == "Future")
+        }
+        _ => false,
+    })
+}
@@ -0,0 +1,185 @@
+// Text edits to a source file, located with the spans of its syntax tree.
+//
+// Rewriting the source text in place, rather than printing the syntax tree
+// again, keeps the formatting and comments of the code around the edits.
+
+use std::ops::Range;
+
+use proc_macro2::{LineColumn, Span};
+
+pub struct Source<'a> {
+    text: &'a str,
+    lines: Vec<usize>,
+}
+
+impl<'a> Source<'a> {
+    pub fn new(text: &'a str) -> Source<'a> {
+        let lines = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(index, _)| index + 1))
+            .collect();
+        Source { text, lines }
+    }
+
+    fn offset(&self, at: LineColumn) -> usize {
+        let line = self.lines[at.line - 1];
+        let column: usize = self.text[line..]
+            .chars()
+            .take(at.column)
+            .map(char::len_utf8)
+            .sum();
+        line + column
+    }
+
+    pub fn start(&self, span: Span) -> usize {
+        self.offset(span.start())
+    }
+
+    pub fn end(&self, span: Span) -> usize {
+        self.offset(span.end())
+    }
+
+    pub fn range(&self, span: Span) -> Range<usize> {
+        self.start(span)..self.end(span)
+    }
+
+    pub fn text(&self, span: Span) -> &'a str {
+        &self.text[self.range(span)]
+    }
+
+    // The line number of an offset, starting from 1
+    pub fn line(&self, offset: usize) -> usize {
+        self.lines.partition_point(|&line| line <= offset)
+    }
+
+    // The whitespace at the start of the line containing an offset
+    pub fn indent(&self, offset: usize) -> &'a str {
+        let line = &self.text[self.lines[self.line(offset) - 1]..];
+        &line[..line.len() - line.trim_start_matches([' ', '\t']).len()]
+    }
+
+    // The offset of the next token or comment at or after an offset
+    pub fn skip_whitespace(&self, offset: usize) -> usize {
+        let rest = &self.text[offset..];
+        offset + rest.len() - rest.trim_start().len()
+    }
+
+    // The offset after the previous token or comment before an offset
+    pub fn skip_whitespace_back(&self, offset: usize) -> usize {
+        self.text[..offset].trim_end().len()
+    }
+}
+
+struct Edit {
+    range: Range<usize>,
+    text: String,
+}
+
+#[derive(Default)]
+pub struct Edits(Vec<Edit>);
+
+impl Edits {
+    pub fn replace(&mut self, range: Range<usize>, text: impl Into<String>) {
+        let text = text.into();
+        self.0.push(Edit { range, text });
+    }
+
+    pub fn insert(&mut self, at: usize, text: impl Into<String>) {
+        self.replace(at..at, text);
+    }
+
+    pub fn delete(&mut self, range: Range<usize>) {
+        self.replace(range, "");
+    }
+
+    pub fn append(&mut self, edits: Edits) {
+        self.0.extend(edits.0);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    // Edits are applied in the order of their ranges, and insertions at the
+    // same offset in the order they were made.
+    fn sorted(&self) -> Vec<&Edit> {
+        let mut edits: Vec<&Edit> = self.0.iter().collect();
+        edits.sort_by_key(|edit| (edit.range.start, edit.range.end));
+        edits
+    }
+
+    pub fn apply(&self, text: &str) -> String {
+        apply(text, 0, &self.sorted())
+    }
+
+    // A diff of the lines changed by the edits, without any lines of context.
+    pub fn diff(&self, path: &str, source: &Source) -> String {
+        let mut hunks: Vec<Hunk> = Vec::new();
+        for edit in self.sorted() {
+            let first = source.line(edit.range.start);
+            let last = source.line(edit.range.end.max(edit.range.start + 1) - 1);
+            match hunks.last_mut() {
+                Some(hunk) if first <= hunk.last + 1 => {
+                    hunk.last = hunk.last.max(last);
+                    hunk.edits.push(edit);
+                }
+                _ => hunks.push(Hunk {
+                    first,
+                    last: last.max(first),
+                    edits: vec![edit],
+                }),
+            }
+        }
+
+        let mut diff = format!("--- {}\n+++ {}\n", path, path);
+        // The number of lines added by the hunks so far, to number the new lines
+        let mut added = 0isize;
+        for hunk in hunks {
+            let start = source.lines[hunk.first - 1];
+            let end = source
+                .lines
+                .get(hunk.last)
+                .copied()
+                .unwrap_or(source.text.len());
+            let old = &source.text[start..end];
+            let new = apply(old, start, &hunk.edits);
+            let (old_count, new_count) = (old.lines().count(), new.lines().count());
+            diff += &format!(
+                "@@ -{},{} +{},{} @@\n",
+                hunk.first,
+                old_count,
+                hunk.first as isize + added,
+                new_count
+            );
+            for line in old.lines() {
+                diff += &format!("-{}\n", line);
+            }
+            for line in new.lines() {
+                diff += &format!("+{}\n", line);
+            }
+            added += new_count as isize - old_count as isize;
+        }
+        diff
+    }
+}
+
+// The edits to a range of lines, from first to last inclusive
+struct Hunk<'a> {
+    first: usize,
+    last: usize,
+    edits: Vec<&'a Edit>,
+}
+
+// Applies sorted edits to text starting at an offset of the source
+fn apply(text: &str, offset: usize, edits: &[&Edit]) -> String {
+    let mut output = String::new();
+    let mut position = 0;
+    for edit in edits {
+        let range = edit.range.start - offset..edit.range.end - offset;
+        debug_assert!(position <= range.start, "overlapping edits");
+        output += &text[position..range.start];
+        output += &edit.text;
+        position = range.end;
+    }
+    output += &text[position..];
+    output
+}
@@ -0,0 +1,316 @@
+//! Rewrites functions that return a `Result` or `Option` explicitly to use culpa, and back.
+//!
+//! Rewriting a function to use culpa adds a `#[culpa::throws]` attribute to it, replaces its
+//! return type with the ok type, unwraps the values it returns from `Ok` or `Some`, and turns
+//! `Err(error)?` and `return Err(error)` into `culpa::throw!(error)`. Desugaring does the
+//! opposite for functions with a `#[throws]` or `#[try_fn]` attribute.
+//!
+//! Only the edited parts of a file are changed, the formatting and comments of the rest of it are
+//! kept. A function is only rewritten if every value it returns can be rewritten, otherwise it is
+//! left alone and reported as skipped. `const` functions are always skipped, since the functions
+//! culpa generates call trait methods.
+//!
+//! ## Example
+//!
+//! ```
+//! use culpa_migrate::{migrate, Direction};
+//!
+//! let source = "fn parse(input: &str) -> Result<u8, Error> {
+//!     if input.is_empty() {
+//!         return Err(Error::Empty);
+//!     }
+//!     Ok(input.parse()?)
+//! }
+//! ";
+//!
+//! let migration = migrate(source, Direction::Throws).unwrap();
+//! assert_eq!(
+//!     migration.output(),
+//!     "#[culpa::throws(Error)]
+//! fn parse(input: &str) -> u8 {
+//!     if input.is_empty() {
+//!         culpa::throw!(Error::Empty);
+//!     }
+//!     input.parse()?
+//! }
+//! ",
+//! );
+//! ```
+
+mod desugar;
+mod edit;
+mod sugar;
+mod tail;
+
+use std::fmt;
+use std::ops::Range;
+
+use proc_macro2::Span;
+use syn::spanned::Spanned;
+use syn::visit::Visit;
+
+use edit::{Edits, Source};
+
+/// What functions are rewritten into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// Rewrite functions returning a `Result` or `Option` to use `#[throws]`, or `#[try_fn]` if
+    /// their error type is not written out, as in `io::Result<T>`.
+    Throws,
+    /// Rewrite functions returning a `Result` or `Option` to use `#[try_fn]`.
+    TryFn,
+    /// Rewrite functions using `#[throws]` or `#[try_fn]` to return a `Result` or `Option`
+    /// explicitly.
+    Desugar,
+}
+
+/// The result of rewriting the functions in a source file.
+pub struct Migration {
+    text: String,
+    edits: Edits,
+    skipped: Vec<Skipped>,
+}
+
+impl Migration {
+    /// Whether any function was rewritten.
+    pub fn is_changed(&self) -> bool {
+        !self.edits.is_empty()
+    }
+
+    /// The rewritten source file.
+    pub fn output(&self) -> String {
+        self.edits.apply(&self.text)
+    }
+
+    /// A diff of the lines changed by rewriting the source file, labeled with its path.
+    pub fn diff(&self, path: &str) -> String {
+        self.edits.diff(path, &Source::new(&self.text))
+    }
+
+    /// The functions that could not be rewritten.
+    pub fn skipped(&self) -> &[Skipped] {
+        &self.skipped
+    }
+}
+
+/// A function that could not be rewritten.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Skipped {
+    /// The line of the function, starting from 1.
+    pub line: usize,
+    /// The name of the function.
+    pub function: String,
+    /// Why the function could not be rewritten.
+    pub reason: String,
+}
+
+impl fmt::Display for Skipped {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "skipped `{}`: {}", self.function, self.reason)
+    }
+}
+
+/// Rewrites the functions in a source file.
+///
+/// Returns an error if the source file cannot be parsed.
+pub fn migrate(text: &str, direction: Direction) -> syn::Result<Migration> {
+    let file = syn::parse_file(text)?;
+    let source = Source::new(text);
+    let mut edits = Edits::default();
+    let mut skipped = Vec::new();
+    let imports = match direction {
+        Direction::Desugar => desugar::imports(&file),
+        _ => Vec::new(),
+    };
+    Functions(|function: Function| {
+        let result = match direction {
+            Direction::Throws => sugar::sugar(&source, &function, false),
+            Direction::TryFn => sugar::sugar(&source, &function, true),
+            Direction::Desugar => desugar::desugar(&source, &function, &imports),
+        };
+        match result {
+            Some(Ok(function_edits)) => edits.append(function_edits),
+            Some(Err(reason)) => skipped.push(Skipped {
+                line: source.line(source.start(function.start)),
+                function: function.sig.ident.to_string(),
+                reason,
+            }),
+            None => {}
+        }
+    })
+    .visit_file(&file);
+    Ok(Migration {
+        text: text.to_string(),
+        edits,
+        skipped,
+    })
+}
+
+// A function with a body, which may be rewritten
+struct Function<'a> {
+    attrs: &'a [syn::Attribute],
+    // The first token after the attributes
+    start: Span,
+    sig: &'a syn::Signature,
+    block: &'a syn::Block,
+}
+
+// Visits every function with a body, including those nested in other items
+struct Functions<F>(F);
+
+impl<'ast, F: FnMut(Function<'ast>)> Visit<'ast> for Functions<F> {
+    fn visit_item_fn(&mut self, i: &'ast syn::ItemFn) {
+        (self.0)(Function {
+            attrs: &i.attrs,
+            start: start(&i.vis, None, &i.sig),
+            sig: &i.sig,
+            block: &i.block,
+        });
+        syn::visit::visit_item_fn(self, i);
+    }
+
+    fn visit_impl_item_fn(&mut self, i: &'ast syn::ImplItemFn) {
+        (self.0)(Function {
+            attrs: &i.attrs,
+            start: start(&i.vis, i.defaultness.as_ref(), &i.sig),
+            sig: &i.sig,
+            block: &i.block,
+        });
+        syn::visit::visit_impl_item_fn(self, i);
+    }
+
+    fn visit_trait_item_fn(&mut self, i: &'ast syn::TraitItemFn) {
+        if let Some(block) = &i.default {
+            (self.0)(Function {
+                attrs: &i.attrs,
+                start: start(&syn::Visibility::Inherited, None, &i.sig),
+                sig: &i.sig,
+                block,
+            });
+        }
+        syn::visit::visit_trait_item_fn(self, i);
+    }
+}
+
+fn start(
+    vis: &syn::Visibility,
+    defaultness: Option<&syn::Token![default]>,
+    sig: &syn::Signature,
+) -> Span {
+    match (vis, defaultness) {
+        (syn::Visibility::Inherited, Some(defaultness)) => defaultness.span,
+        (syn::Visibility::Inherited, None) => sig.span(),
+        (vis, _) => vis.span(),
+    }
+}
+
+// What a function returns, which determines how the values it returns are
+// wrapped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Result,
+    Option,
+}
+
+impl Kind {
+    fn ok(self) -> &'static str {
+        match self {
+            Kind::Result => "Ok",
+            Kind::Option => "Some",
+        }
+    }
+}
+
+// The kind of a `Result` or `Option` return type, with its ok and error types
+fn returned(ty: &syn::Type) -> Option<(Kind, &syn::Type, Option<&syn::Type>)> {
+    let segment = match ty {
+        syn::Type::Path(ty) if ty.qself.is_none() => ty.path.segments.last()?,
+        _ => return None,
+    };
+    let kind = if segment.ident == "Result" {
+        Kind::Result
+    } else if segment.ident == "Option" {
+        Kind::Option
+    } else {
+        return None;
+    };
+    let mut types = match &segment.arguments {
+        syn::PathArguments::AngleBracketed(args) => args.args.iter().filter_map(|arg| match arg {
+            syn::GenericArgument::Type(ty) => Some(ty),
+            _ => None,
+        }),
+        _ => return None,
+    };
+    Some((kind, types.next()?, types.next()))
+}
+
+// The index of a `#[throws]` or `#[try_fn]` attribute
+fn culpa_attr(attrs: &[syn::Attribute]) -> Option<usize> {
+    attrs
+        .iter()
+        .position(|attr| is_attr(attr, "throws") || is_attr(attr, "try_fn"))
+}
+
+fn is_attr(attr: &syn::Attribute, name: &str) -> bool {
+    attr.path().segments.last().unwrap().ident == name
+}
+
+// A call to a constructor with a single argument, like `Ok(x)`
+fn constructor(expr: &syn::Expr) -> Option<(&syn::Ident, &syn::ExprCall)> {
+    let call = match expr {
+        syn::Expr::Call(call) if call.args.len() == 1 => call,
+        _ => return None,
+    };
+    match &*call.func {
+        syn::Expr::Path(path) if path.qself.is_none() => {
+            Some((&path.path.segments.last()?.ident, call))
+        }
+        _ => None,
+    }
+}
+
+fn is_none(expr: &syn::Expr) -> bool {
+    matches!(expr, syn::Expr::Path(path) if path.path.is_ident("None"))
+}
+
+fn is_unit(expr: &syn::Expr) -> bool {
+    matches!(expr, syn::Expr::Tuple(tuple) if tuple.elems.is_empty())
+}
+
+fn macro_name(mac: &syn::Macro) -> &syn::Ident {
+    &mac.path.segments.last().unwrap().ident
+}
+
+// Whether an expression never evaluates to a value
+fn diverges(expr: &syn::Expr) -> bool {
+    match expr {
+        syn::Expr::Return(_) | syn::Expr::Break(_) | syn::Expr::Continue(_) => true,
+        syn::Expr::Macro(expr) => diverging_macro(&expr.mac),
+        _ => false,
+    }
+}
+
+fn diverging_macro(mac: &syn::Macro) -> bool {
+    let name = macro_name(mac);
+    [
+        "panic",
+        "unreachable",
+        "todo",
+        "unimplemented",
+        "throw",
+        "bail",
+    ]
+    .iter()
+    .any(|diverging| name == diverging)
+}
+
+// The ranges of a call before and after its argument, like `Ok(` and `)` in
+// `Ok(x)`, which are replaced to unwrap the argument or wrap it in something
+// else.
+fn call_wrapper(source: &Source, call: &syn::ExprCall) -> (Range<usize>, Range<usize>) {
+    let arg = &call.args[0];
+    (
+        source.start(call.span())..source.start(arg.span()),
+        source.end(arg.span())..source.end(call.span()),
+    )
+}
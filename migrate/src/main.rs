@@ -0,0 +1,102 @@
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::{env, fs, io, process};
+
+use culpa_migrate::{migrate, Direction};
+
+const USAGE: &str = "\
+usage: culpa-migrate [--try-fn | --desugar] [--dry-run] <path>...
+
+Rewrites the functions returning a `Result` or `Option` in the given files, and
+the Rust files in the given directories, to use `#[culpa::throws]`.
+
+options:
+    --try-fn    use `#[culpa::try_fn]` instead, keeping the return types
+    --desugar   rewrite functions using culpa back to returning `Result` or `Option`
+    --dry-run   print a diff of the changes instead of writing them";
+
+fn main() {
+    let mut direction = Direction::Throws;
+    let mut dry_run = false;
+    let mut paths = Vec::new();
+    for arg in env::args().skip(1) {
+        match arg.as_str() {
+            "--try-fn" => direction = Direction::TryFn,
+            "--desugar" => direction = Direction::Desugar,
+            "--dry-run" => dry_run = true,
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            }
+            arg if arg.starts_with('-') => usage_error(&format!("unknown option `{}`", arg)),
+            _ => paths.push(PathBuf::from(arg)),
+        }
+    }
+    if paths.is_empty() {
+        usage_error("no paths given");
+    }
+
+    let mut failed = false;
+    let mut files = Vec::new();
+    for path in paths {
+        if let Err(error) = collect(&path, &mut files) {
+            eprintln!("{}: {}", path.display(), error);
+            failed = true;
+        }
+    }
+    for file in files {
+        if let Err(error) = run(&file, direction, dry_run) {
+            eprintln!("{}: {}", file.display(), error);
+            failed = true;
+        }
+    }
+    if failed {
+        process::exit(1);
+    }
+}
+
+fn usage_error(message: &str) -> ! {
+    eprintln!("error: {}\n\n{}", message, USAGE);
+    process::exit(2);
+}
+
+fn run(path: &Path, direction: Direction, dry_run: bool) -> Result<(), Box<dyn Error>> {
+    let text = fs::read_to_string(path)?;
+    let migration = migrate(&text, direction)?;
+    for skipped in migration.skipped() {
+        eprintln!("{}:{}: {}", path.display(), skipped.line, skipped);
+    }
+    if !migration.is_changed() {
+        return Ok(());
+    }
+    if dry_run {
+        print!("{}", migration.diff(&path.display().to_string()));
+    } else {
+        fs::write(path, migration.output())?;
+    }
+    Ok(())
+}
+
+// Collects the Rust files at a path, searching directories recursively except
+// for hidden directories and build output in `target`
+fn collect(path: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    if !path.is_dir() {
+        files.push(path.to_path_buf());
+        return Ok(());
+    }
+    let mut entries = fs::read_dir(path)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<io::Result<Vec<_>>>()?;
+    entries.sort();
+    for entry in entries {
+        let name = entry.file_name().unwrap_or_default().to_string_lossy();
+        if entry.is_dir() {
+            if !name.starts_with('.') && name != "target" {
+                collect(&entry, files)?;
+            }
+        } else if entry.extension() == Some("rs".as_ref()) {
+            files.push(entry);
+        }
+    }
+    Ok(())
+}
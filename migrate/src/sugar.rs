@@ -0,0 +1,191 @@
+// Rewrites a function returning a `Result` or `Option` to use culpa.
+//
+// The values the function returns are unwrapped from `Ok` or `Some`, and its
+// errors are thrown with `throw!`. Returned `Ok(())`s at the end of blocks are
+// removed entirely, since the blocks then evaluate to `()` themselves.
+
+use syn::spanned::Spanned;
+use syn::visit::Visit;
+
+use crate::edit::{Edits, Source};
+use crate::tail::{self, Leaf};
+use crate::{Function, Kind};
+
+pub fn sugar(source: &Source, function: &Function, try_fn: bool) -> Option<Result<Edits, String>> {
+    if crate::culpa_attr(function.attrs).is_some() {
+        return None;
+    }
+    let ty = match &function.sig.output {
+        syn::ReturnType::Type(_, ty) => ty,
+        syn::ReturnType::Default => return None,
+    };
+    let (kind, ok, error) = crate::returned(ty)?;
+    // The transformed body calls trait methods, which const functions cannot
+    if function.sig.constness.is_some() {
+        return Some(Err("const functions are not supported".to_string()));
+    }
+
+    let mut body = Body {
+        source,
+        kind,
+        edits: Edits::default(),
+        error: None,
+    };
+    for leaf in tail::leaves(function.block) {
+        body.leaf(leaf);
+    }
+    body.visit_block(function.block);
+    if let Some(error) = body.error {
+        return Some(Err(error));
+    }
+    let mut edits = body.edits;
+
+    // A `Result` alias with the error type built in, like `io::Result<T>`,
+    // keeps its return type with `#[try_fn]`
+    let attr = match (kind, error) {
+        _ if try_fn => None,
+        (Kind::Result, Some(error)) => {
+            Some(format!("#[culpa::throws({})]", source.text(error.span())))
+        }
+        (Kind::Result, None) => None,
+        (Kind::Option, _) => Some("#[culpa::throws(as Option)]".to_string()),
+    };
+    let attr = match attr {
+        Some(attr) => {
+            if is_unit_type(ok) {
+                let params = source.end(function.sig.paren_token.span.close());
+                edits.delete(params..source.end(ty.span()));
+            } else {
+                edits.replace(source.range(ty.span()), source.text(ok.span()));
+            }
+            attr
+        }
+        None => "#[culpa::try_fn]".to_string(),
+    };
+    let start = source.start(function.start);
+    edits.insert(start, format!("{}\n{}", attr, source.indent(start)));
+    Some(Ok(edits))
+}
+
+struct Body<'a> {
+    source: &'a Source<'a>,
+    kind: Kind,
+    edits: Edits,
+    error: Option<String>,
+}
+
+impl Body<'_> {
+    fn leaf(&mut self, leaf: Leaf) {
+        let expr = match leaf {
+            Leaf::Tail(expr) | Leaf::Arm(expr) => expr,
+            // A block evaluating to `()` in a function returning a `Result`
+            // or `Option` must diverge
+            Leaf::Empty(_) => return,
+        };
+        match crate::constructor(expr) {
+            Some((name, call)) if name == self.kind.ok() => {
+                if !crate::is_unit(&call.args[0]) {
+                    self.unwrap(call);
+                } else if let Leaf::Tail(_) = leaf {
+                    let range = self.source.range(expr.span());
+                    let start = self.source.skip_whitespace_back(range.start);
+                    self.edits.delete(start..range.end);
+                } else {
+                    self.edits.replace(self.source.range(expr.span()), "{}");
+                }
+            }
+            Some((name, call)) if name == "Err" && self.kind == Kind::Result => {
+                self.throw(self.source.start(call.span()), call);
+            }
+            _ if crate::is_none(expr) && self.kind == Kind::Option => {
+                self.edits
+                    .replace(self.source.range(expr.span()), "culpa::throw!()");
+            }
+            _ if crate::diverges(expr) => {}
+            _ => self.fail(),
+        }
+    }
+
+    // Removes the constructor around a returned value
+    fn unwrap(&mut self, call: &syn::ExprCall) {
+        let (before, after) = crate::call_wrapper(self.source, call);
+        self.edits.delete(before);
+        self.edits.delete(after);
+    }
+
+    // Replaces everything from `start` up to the error in `Err(error)` with a
+    // `throw!` of it. The error is converted by `throw!`, so an `.into()` on
+    // it is removed, or the type to convert into would be ambiguous.
+    fn throw(&mut self, start: usize, call: &syn::ExprCall) {
+        let (before, mut after) = crate::call_wrapper(self.source, call);
+        self.edits.replace(start..before.end, "culpa::throw!(");
+        if let syn::Expr::MethodCall(into) = &call.args[0] {
+            if into.method == "into" && into.args.is_empty() && into.turbofish.is_none() {
+                after.start = self.source.end(into.receiver.span());
+            }
+        }
+        self.edits.replace(after, ")");
+    }
+
+    fn fail(&mut self) {
+        if self.error.is_none() {
+            self.error = Some(format!(
+                "it returns a value that is not wrapped in `{}`",
+                self.kind.ok()
+            ));
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for Body<'_> {
+    // Closures, async blocks and nested items return their own values
+
+    fn visit_expr_closure(&mut self, _: &'ast syn::ExprClosure) {}
+
+    fn visit_expr_async(&mut self, _: &'ast syn::ExprAsync) {}
+
+    fn visit_item(&mut self, _: &'ast syn::Item) {}
+
+    fn visit_expr_return(&mut self, i: &'ast syn::ExprReturn) {
+        if let Some(expr) = &i.expr {
+            match crate::constructor(expr) {
+                Some((name, call)) if name == self.kind.ok() => {
+                    if crate::is_unit(&call.args[0]) {
+                        let start = self.source.end(i.return_token.span);
+                        self.edits.delete(start..self.source.end(expr.span()));
+                    } else {
+                        self.unwrap(call);
+                    }
+                }
+                Some((name, call)) if name == "Err" && self.kind == Kind::Result => {
+                    self.throw(self.source.start(i.return_token.span), call);
+                }
+                _ if crate::is_none(expr) && self.kind == Kind::Option => {
+                    self.edits
+                        .replace(self.source.range(i.span()), "culpa::throw!()");
+                }
+                _ => self.fail(),
+            }
+        }
+        syn::visit::visit_expr_return(self, i);
+    }
+
+    fn visit_expr_try(&mut self, i: &'ast syn::ExprTry) {
+        match crate::constructor(&i.expr) {
+            Some((name, call)) if name == "Err" && self.kind == Kind::Result => {
+                self.throw(self.source.start(call.span()), call);
+                self.edits.delete(self.source.range(i.question_token.span));
+            }
+            _ if crate::is_none(&i.expr) && self.kind == Kind::Option => {
+                self.edits
+                    .replace(self.source.range(i.span()), "culpa::throw!()");
+            }
+            _ => {}
+        }
+        syn::visit::visit_expr_try(self, i);
+    }
+}
+
+fn is_unit_type(ty: &syn::Type) -> bool {
+    matches!(ty, syn::Type::Tuple(tuple) if tuple.elems.is_empty())
+}
@@ -0,0 +1,51 @@
+// Finds the expressions a function body evaluates to, which are what it
+// returns besides `return` expressions. These are the tail expressions of the
+// body, looking through the branches of any `if` or `match` it ends with.
+
+pub enum Leaf<'a> {
+    // The tail expression of a block
+    Tail(&'a syn::Expr),
+    // The body of a match arm that is not a block
+    Arm(&'a syn::Expr),
+    // A block without a tail expression, which evaluates to `()` unless its
+    // last statement diverges
+    Empty(&'a syn::Block),
+}
+
+pub fn leaves(body: &syn::Block) -> Vec<Leaf<'_>> {
+    let mut leaves = Vec::new();
+    block(body, &mut leaves);
+    leaves
+}
+
+fn block<'a>(block: &'a syn::Block, leaves: &mut Vec<Leaf<'a>>) {
+    match block.stmts.last() {
+        Some(syn::Stmt::Expr(tail, None)) => expr(true, tail, leaves),
+        _ => leaves.push(Leaf::Empty(block)),
+    }
+}
+
+// Finds the leaves of an expression, which is either the tail of a block or
+// the body of a match arm
+fn expr<'a>(tail: bool, expr: &'a syn::Expr, leaves: &mut Vec<Leaf<'a>>) {
+    match expr {
+        // A labeled block can be broken out of with a value, so it is a leaf
+        syn::Expr::Block(expr) if expr.label.is_none() => block(&expr.block, leaves),
+        syn::Expr::Unsafe(expr) => block(&expr.block, leaves),
+        syn::Expr::If(syn::ExprIf {
+            then_branch,
+            else_branch: Some((_, else_branch)),
+            ..
+        }) => {
+            block(then_branch, leaves);
+            self::expr(false, else_branch, leaves);
+        }
+        syn::Expr::Match(expr) => {
+            for arm in &expr.arms {
+                self::expr(false, &arm.body, leaves);
+            }
+        }
+        expr if tail => leaves.push(Leaf::Tail(expr)),
+        expr => leaves.push(Leaf::Arm(expr)),
+    }
+}
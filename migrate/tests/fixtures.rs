@@ -0,0 +1,96 @@
+use std::fs;
+use std::path::Path;
+
+use culpa_migrate::{migrate, Direction};
+
+// Migrates each `NAME.rs` in a fixture directory, comparing the output with
+// `NAME.expected` and the skipped functions with `NAME.skipped`. Run with
+// `MIGRATE=overwrite` to update them.
+fn check(dir: &str, direction: Direction) {
+    let overwrite = std::env::var("MIGRATE").as_deref() == Ok("overwrite");
+    let dir = Path::new("tests/fixtures").join(dir);
+    let mut inputs: Vec<_> = fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension() == Some("rs".as_ref()))
+        .collect();
+    inputs.sort();
+    assert!(!inputs.is_empty(), "no fixtures in {}", dir.display());
+
+    for input in inputs {
+        let migration = migrate(&fs::read_to_string(&input).unwrap(), direction).unwrap();
+        let skipped: String = migration
+            .skipped()
+            .iter()
+            .map(|skipped| format!("{}: {}\n", skipped.line, skipped))
+            .collect();
+        for (extension, actual) in [("expected", migration.output()), ("skipped", skipped)] {
+            let path = input.with_extension(extension);
+            if overwrite {
+                if actual.is_empty() && extension == "skipped" {
+                    let _ = fs::remove_file(&path);
+                } else {
+                    fs::write(&path, &actual).unwrap();
+                }
+                continue;
+            }
+            let expected = fs::read_to_string(&path).unwrap_or_default();
+            assert_eq!(expected, actual, "{}", path.display());
+        }
+    }
+}
+
+#[test]
+fn throws() {
+    check("throws", Direction::Throws);
+}
+
+#[test]
+fn try_fn() {
+    check("try_fn", Direction::TryFn);
+}
+
+#[test]
+fn desugar() {
+    check("desugar", Direction::Desugar);
+}
+
+#[test]
+fn diff() {
+    let source = "\
+fn one() -> Result<u8, Error> {
+    Ok(1)
+}
+
+fn unit() -> Result<(), Error> {
+    one()?;
+    Ok(())
+}
+";
+    let migration = migrate(source, Direction::Throws).unwrap();
+    assert_eq!(
+        migration.diff("src/lib.rs"),
+        "\
+--- src/lib.rs
++++ src/lib.rs
+@@ -1,2 +1,3 @@
+-fn one() -> Result<u8, Error> {
+-    Ok(1)
++#[culpa::throws(Error)]
++fn one() -> u8 {
++    1
+@@ -5,3 +6,3 @@
+-fn unit() -> Result<(), Error> {
+-    one()?;
+-    Ok(())
++#[culpa::throws(Error)]
++fn unit() {
++    one()?;
+",
+    );
+}
+
+#[test]
+fn parse_error() {
+    assert!(migrate("fn broken(", Direction::Throws).is_err());
+}
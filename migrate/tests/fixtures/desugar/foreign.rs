@@ -0,0 +1,31 @@
+use anyhow::bail;
+use culpa::throws;
+
+#[derive(Debug)]
+pub enum Error {
+    Empty,
+}
+
+#[throws(Error)]
+fn qualified(text: &str) -> usize {
+    if text.is_empty() {
+        culpa::throw!(Error::Empty);
+    }
+    text.len()
+}
+
+#[throws(anyhow::Error)]
+fn imported(text: &str) -> usize {
+    if text.is_empty() {
+        bail!("empty");
+    }
+    text.len()
+}
+
+#[throws(anyhow::Error)]
+fn other_crate(text: &str) -> usize {
+    if text.is_empty() {
+        anyhow::bail!("empty");
+    }
+    text.len()
+}
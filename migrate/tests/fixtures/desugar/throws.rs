@@ -0,0 +1,88 @@
+use culpa::{throw, throws, try_fn};
+
+#[derive(Debug)]
+pub enum Error {
+    Empty,
+}
+
+/// Reads the configuration.
+#[throws(Error)]
+#[inline]
+pub fn read(path: &str) -> String {
+    let text = std::fs::read_to_string(path).map_err(|_| Error::Empty)?;
+    if text.is_empty() {
+        throw!(Error::Empty);
+    }
+    text
+}
+
+#[throws]
+fn check(text: &str) {
+    if text.is_empty() {
+        return;
+    }
+    read(text)?;
+}
+
+#[throws(_)]
+fn branches(value: u8) -> u8 {
+    if value == 0 {
+        throw!(Error::Empty)
+    } else if value < 10 {
+        value
+    } else {
+        match value {
+            10 => 10,
+            11 => return 11,
+            _ => unreachable!(),
+        }
+    }
+}
+
+#[throws(as Option)]
+fn first(values: &[u8]) -> u8 {
+    let first = *values.first()?;
+    if first == 0 {
+        throw!();
+    }
+    first
+}
+
+#[try_fn]
+fn alias() -> std::io::Result<()> {
+    let closure = || 1;
+    std::fs::read_to_string(closure().to_string())?;
+}
+
+#[throws]
+fn annotated_closure() {
+    let closure = #[throws(Error)] || 1;
+    closure()?;
+}
+
+#[throws(Error)]
+fn empty() {}
+
+pub struct Store(Vec<u8>);
+
+impl Store {
+    #[throws(Error)]
+    pub fn get(&self, index: usize) -> u8 {
+        match self.0.get(index) {
+            Some(value) => *value,
+            None => throw!(Error::Empty),
+        }
+    }
+}
+
+#[throws(std::io::Error | Error)]
+fn several() {}
+
+#[throws(Error as std::task::Poll)]
+fn poll() -> u8 {
+    std::task::Poll::Ready(1)
+}
+
+fn plain() -> u8 {
+    1
+}
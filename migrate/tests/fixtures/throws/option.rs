@@ -0,0 +1,21 @@
+fn first(values: &[u8]) -> Option<u8> {
+    let first = *values.first()?;
+    if first == 0 {
+        return None;
+    }
+    Some(first)
+}
+
+fn second(values: &[u8]) -> Option<u8> {
+    match values {
+        [_, second, ..] => Some(*second),
+        _ => None,
+    }
+}
+
+fn third(values: Option<&[u8]>) -> Option<()> {
+    if values.is_none() {
+        None?;
+    }
+    Some(())
+}
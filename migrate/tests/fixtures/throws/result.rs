@@ -0,0 +1,97 @@
+use std::io;
+
+#[derive(Debug)]
+pub enum Error {
+    Empty,
+    Io(io::Error),
+}
+
+/// Reads the configuration.
+#[inline]
+pub fn read(path: &str) -> Result<String, Error> {
+    let text = std::fs::read_to_string(path).map_err(Error::Io)?;
+    if text.is_empty() {
+        return Err(Error::Empty);
+    }
+    Ok(text)
+}
+
+fn check(text: &str) -> Result<(), Error> {
+    if text.is_empty() {
+        Err(Error::Empty)?;
+    }
+    // Only the `Ok(())` is removed
+    Ok(())
+}
+
+fn branches(value: u8) -> Result<u8, Error> {
+    if value == 0 {
+        Err(Error::Empty)
+    } else if value < 10 {
+        Ok(value)
+    } else {
+        match value {
+            10 => Ok(10),
+            11 => return Ok(11),
+            _ => unreachable!(),
+        }
+    }
+}
+
+fn unit_arms(value: u8) -> Result<(), Error> {
+    match value {
+        0 => Ok(()),
+        _ => {
+            check("")?;
+            Ok(())
+        }
+    }
+}
+
+fn early_unit(value: u8) -> Result<(), Error> {
+    if value == 0 {
+        return Ok(());
+    }
+    let closure = |value: u8| -> Result<u8, Error> { Ok(value) };
+    closure(value)?;
+    Ok(())
+}
+
+fn alias() -> io::Result<u8> {
+    Ok(1)
+}
+
+pub struct Store(Vec<u8>);
+
+impl Store {
+    pub fn get(&self, index: usize) -> Result<u8, Error> {
+        match self.0.get(index) {
+            Some(value) => Ok(*value),
+            None => Err(Error::Empty),
+        }
+    }
+}
+
+fn forwarded() -> Result<u8, Error> {
+    branches(1)
+}
+
+fn mixed(value: u8) -> Result<u8, Error> {
+    if value == 0 {
+        return branches(value);
+    }
+    Ok(value)
+}
+
+const fn constant() -> Result<u8, Error> {
+    Ok(1)
+}
+
+#[culpa::throws(Error)]
+fn migrated() -> u8 {
+    1
+}
+
+fn infallible() -> u8 {
+    1
+}
@@ -0,0 +1,18 @@
+use std::io;
+
+fn read(path: &str) -> Result<String, io::Error> {
+    let text = std::fs::read_to_string(path)?;
+    if text.is_empty() {
+        return Err(io::ErrorKind::InvalidData.into());
+    }
+    Ok(text)
+}
+
+fn first(values: &[u8]) -> Option<u8> {
+    Some(*values.first()?)
+}
+
+fn alias() -> io::Result<()> {
+    read("config.toml")?;
+    Ok(())
+}
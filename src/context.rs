@@ -0,0 +1,141 @@
+use core::fmt;
+
+/// An error with a message describing what was being done when it occurred.
+///
+/// This is constructed by the [`context!`](crate::context!) macro, by `throw!` when it is given a
+/// message after the error, or by [`ResultExt::context`].
+pub struct Context<E> {
+    message: Message,
+    error: E,
+}
+
+enum Message {
+    Static(&'static str),
+    #[cfg(feature = "alloc")]
+    Owned(alloc::string::String),
+}
+
+impl<E> Context<E> {
+    /// Attaches a message to an error.
+    pub fn new(message: &'static str, error: E) -> Context<E> {
+        Context {
+            message: Message::Static(message),
+            error,
+        }
+    }
+
+    /// Attaches a formatted message to an error.
+    ///
+    /// This only allocates if the message has arguments.
+    #[cfg(feature = "alloc")]
+    pub fn from_args(message: fmt::Arguments<'_>, error: E) -> Context<E> {
+        let message = match message.as_str() {
+            Some(message) => Message::Static(message),
+            None => Message::Owned(alloc::fmt::format(message)),
+        };
+        Context { message, error }
+    }
+
+    /// The message describing what was being done.
+    pub fn message(&self) -> &str {
+        match &self.message {
+            Message::Static(message) => message,
+            #[cfg(feature = "alloc")]
+            Message::Owned(message) => message,
+        }
+    }
+
+    /// The error that occurred.
+    pub fn error(&self) -> &E {
+        &self.error
+    }
+
+    /// Discards the message, returning the error.
+    pub fn into_error(self) -> E {
+        self.error
+    }
+}
+
+impl<E> fmt::Display for Context<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
+impl<E: fmt::Debug> fmt::Debug for Context<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Context")
+            .field("message", &self.message())
+            .field("error", &self.error)
+            .finish()
+    }
+}
+
+impl<E: core::error::Error + 'static> core::error::Error for Context<E> {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// Extends `Result` with methods to attach context to its error.
+pub trait ResultExt<T, E> {
+    /// Attaches a message to the error.
+    fn context(self, message: &'static str) -> Result<T, Context<E>>;
+
+    /// Attaches a message to the error, which is only built if there is an error.
+    #[cfg(feature = "alloc")]
+    fn with_context<F>(self, message: F) -> Result<T, Context<E>>
+    where
+        F: FnOnce() -> alloc::string::String;
+}
+
+impl<T, E> ResultExt<T, E> for Result<T, E> {
+    fn context(self, message: &'static str) -> Result<T, Context<E>> {
+        self.map_err(|error| Context::new(message, error))
+    }
+
+    #[cfg(feature = "alloc")]
+    fn with_context<F>(self, message: F) -> Result<T, Context<E>>
+    where
+        F: FnOnce() -> alloc::string::String,
+    {
+        self.map_err(|error| Context {
+            message: Message::Owned(message()),
+            error,
+        })
+    }
+}
+
+/// Attaches a message to an error, constructing a [`Context`].
+///
+/// The message is a format string followed by its arguments. Without the `alloc` feature it must
+/// be a plain string literal.
+///
+/// ```
+/// let error = culpa::context!(std::fmt::Error, "writing the {}", "header");
+/// assert_eq!(error.to_string(), "writing the header");
+/// ```
+#[cfg(feature = "alloc")]
+#[macro_export]
+macro_rules! context {
+    ($err:expr, $($message:tt)+) => {
+        $crate::Context::from_args(::core::format_args!($($message)+), $err)
+    };
+}
+
+/// Attaches a message to an error, constructing a [`Context`].
+///
+/// The message is a format string followed by its arguments. Without the `alloc` feature it must
+/// be a plain string literal.
+///
+/// ```
+/// let error = culpa::context!(std::fmt::Error, "writing the header");
+/// assert_eq!(error.to_string(), "writing the header");
+/// ```
+#[cfg(not(feature = "alloc"))]
+#[macro_export]
+macro_rules! context {
+    ($err:expr, $message:literal $(,)?) => {
+        $crate::Context::new($message, $err)
+    };
+}
@@ -48,6 +48,11 @@
 //! }
 //! ```
 //!
+//! Since the returned values are ok-wrapped, returning `Ok(x)` explicitly, as in a function that
+//! has not been converted yet, would wrap it twice. A warning is emitted for tail and return
+//! expressions that are explicitly wrapped in `Ok`, `Some` or `Err`, unless the ok type of the
+//! function is itself a `Result` or `Option`, or a type parameter that could be one.
+//!
 //! # `throws` Default Error Type
 //!
 //! The `throws` macro supports a "default error type" - if you do not pass a type to the macro, it
@@ -71,6 +76,85 @@
 //! }
 //! ```
 //!
+//! The default error type can also be declared explicitly for an inline module with
+//! `#[culpa::config(error = MyError)]`, so that it does not have to be named `Error`. The
+//! `throws` attributes, `culpa::main`, `throws_closure!`, `throws_async!` and `try_block!`
+//! without an error type or with `_` inside the module use the declared type instead, including
+//! in nested modules, unless they have their own `#[culpa::config]`. The type is named where
+//! those macros are, so nested modules need it in scope too. It can also take a `crate = path`
+//! argument to use in all of the macros in the module, see [Renamed and re-exported
+//! culpa](#renamed-and-re-exported-culpa). The macros are recognized when they are named through
+//! `culpa::` or that path, or named alone after a `use` item of the module, or of a block around
+//! them, imports them from culpa, so macros of the same names from other crates are left alone.
+//! Macros inside the invocations of other macros are not configured.
+//!
+//! ## Example
+//!
+//! ```
+//! #[culpa::config(error = ParseError)]
+//! mod parsing {
+//!     // Not the default error type anymore
+//!     #[allow(unused_imports)]
+//!     use std::error::Error;
+//!
+//!     use culpa::throws;
+//!
+//!     #[derive(Debug)]
+//!     pub struct ParseError;
+//!
+//!     impl From<std::num::ParseIntError> for ParseError {
+//!         fn from(_: std::num::ParseIntError) -> ParseError {
+//!             ParseError
+//!         }
+//!     }
+//!
+//!     #[throws]
+//!     pub fn parse(input: &str) -> u8 {
+//!         input.parse()?
+//!     }
+//! }
+//!
+//! assert!(parsing::parse("a").is_err());
+//! ```
+//!
+//! # Throwing one of several errors
+//!
+//! A function that can fail with a few unrelated error types can list them separated by `|`. The
+//! macro then generates an enum named after the function, with a variant holding each error
+//! type, and uses it as the error type of the function. Each variant is named after its type, or
+//! after the module of types just named `Error`, like `io::Error`.
+//!
+//! The enum is named after the function in camel case followed by `Error`, so `read_port` throws
+//! a `ReadPortError`. It is declared next to the function, so no other item in the same scope can
+//! have that name. It does not have the generic parameters of the function, so the error types
+//! cannot use them.
+//!
+//! The enum has a `From` impl for each of the error types, so `?` and `throw!` convert to it, and
+//! forwards `Debug`, `Display` and `Error` to the error it holds, when all of the error types
+//! implement them. It has the same visibility as the function, so this is only available on free
+//! functions and not on methods.
+//!
+//! ## Example
+//!
+//! ```
+//! use std::num::ParseIntError;
+//! use std::{fs, io};
+//!
+//! use culpa::throws;
+//!
+//! // Returns `Result<u16, ReadPortError>`
+//! #[throws(io::Error | ParseIntError)]
+//! fn read_port(path: &str) -> u16 {
+//!     fs::read_to_string(path)?.trim().parse()?
+//! }
+//!
+//! match read_port("port.txt") {
+//!     Ok(port) => println!("port {}", port),
+//!     Err(ReadPortError::Io(error)) => println!("could not read the port: {}", error),
+//!     Err(ReadPortError::ParseIntError(error)) => println!("invalid port: {}", error),
+//! }
+//! ```
+//!
 //! # Throwing as an Option
 //!
 //! This syntax can also support functions which return an `Option` instead of a `Result`. To use
@@ -78,7 +162,10 @@
 //! `try_fn` just put it as the return type like normal
 //!
 //! In functions that return `Option`, you can use the `throw!()` macro without any argument to
-//! return `None`.
+//! return `None`. Throwing is supported for any return type with such an empty failure state, as
+//! described by the [`ThrowEmpty`] trait, and it is a compile error for other types like
+//! `Result<T, E>`. The error type of an `Option` is [`NoneError`], so `throw!(None)` and
+//! `throw!(NoneError)` work as well.
 //!
 //! ## Example
 //!
@@ -100,19 +187,525 @@
 //! }
 //! ```
 //!
+//! # Throwing as a `Result<Option<T>, E>`
+//!
+//! Functions that can fail but can also find nothing, like lookups, can return
+//! `Result<Option<T>, E>` with `as ResultOption` after the error type. The body evaluates to the
+//! value found, which is returned as `Ok(Some(value))`, `throw!()` returns `Ok(None)` and
+//! `throw!(error)` returns the error converted with `From`. `?` works on both kinds of values in
+//! the body: a `None` returns `Ok(None)` and an error is converted and thrown like in other
+//! functions.
+//!
+//! ## Example
+//!
+//! ```
+//! use std::collections::HashMap;
+//! use std::num::ParseIntError;
+//!
+//! #[culpa::throws(ParseIntError as ResultOption)]
+//! fn setting(settings: &HashMap<&str, &str>, key: &str) -> u32 {
+//!     if key.is_empty() {
+//!         culpa::throw!();
+//!     }
+//!     settings.get(key)?.parse()?
+//! }
+//!
+//! let settings = HashMap::from([("width", "80"), ("height", "tall")]);
+//! assert_eq!(setting(&settings, "width"), Ok(Some(80)));
+//! assert_eq!(setting(&settings, "depth"), Ok(None));
+//! assert!(setting(&settings, "height").is_err());
+//! ```
+//!
+//! # Returning `impl Trait`
+//!
+//! The return type of a throwing function can be or contain an `impl Trait`, including in trait
+//! methods. The error type can be an `impl Trait` too, but thrown errors are then not converted
+//! with `From`, since there is no concrete type to convert them into: every `throw!` and `?` in
+//! the function must throw the same type, which becomes the hidden error type.
+//!
+//! ## Example
+//!
+//! ```
+//! use culpa::{throw, throws};
+//!
+//! #[derive(Debug)]
+//! struct Empty;
+//!
+//! #[throws(impl std::fmt::Debug)]
+//! fn words(input: &str) -> impl Iterator<Item = &str> {
+//!     if input.is_empty() {
+//!         throw!(Empty);
+//!     }
+//!     input.split(' ')
+//! }
+//! ```
+//!
+//! # Annotating many functions at once
+//!
+//! `throws` and `try_fn` can also be applied to an `impl` block, a trait or an inline module, which
+//! is the same as applying them to every function directly inside it. Functions that should not
+//! be transformed can be marked with `#[nothrow]`, and functions with their own `throws` or
+//! `try_fn` attribute are left to it, so they can use a different error type.
+//!
+//! ## Example
+//!
+//! ```
+//! use culpa::{nothrow, throw, throws};
+//!
+//! #[derive(Debug)]
+//! pub struct StoreError;
+//!
+//! pub struct Store(Vec<u32>);
+//!
+//! #[throws(StoreError)]
+//! impl Store {
+//!     pub fn get(&self, index: usize) -> u32 {
+//!         match self.0.get(index) {
+//!             Some(value) => *value,
+//!             None => throw!(StoreError),
+//!         }
+//!     }
+//!
+//!     pub fn sum(&self, indices: &[usize]) -> u32 {
+//!         let mut sum = 0;
+//!         for &index in indices {
+//!             sum += self.get(index)?;
+//!         }
+//!         sum
+//!     }
+//!
+//!     #[nothrow]
+//!     pub fn count(&self) -> usize {
+//!         self.0.len()
+//!     }
+//! }
+//! ```
+//!
+//! # Throwing closures
+//!
+//! Closures inside a `throws` or `try_fn` function are not transformed along with the function,
+//! a `return` inside them returns from the closure as normal. To make a closure throw, annotate it
+//! with `#[throws]` or `#[try_fn]` just like a function. If the closure has no return type, the
+//! ok type is inferred.
+//!
+//! Outside of a throwing function attributes cannot be applied to closures, so the
+//! `throws_closure!` macro is available instead. It takes the same arguments as `throws`,
+//! separated from the closure by `=>`.
+//!
+//! ## Example
+//!
+//! ```
+//! use culpa::{throw, throws, throws_closure};
+//!
+//! #[throws(std::num::ParseIntError)]
+//! fn sum(input: &str) -> i32 {
+//!     let parse = #[throws(std::num::ParseIntError)]
+//!     |s: &str| -> i32 {
+//!         if s.is_empty() {
+//!             return 0;
+//!         }
+//!         s.trim().parse()?
+//!     };
+//!     input.split(',').map(parse).sum::<Result<i32, _>>()?
+//! }
+//!
+//! fn check(values: &[i32]) -> Result<(), &'static str> {
+//!     values.iter().try_for_each(throws_closure!(&'static str => |&value| {
+//!         if value < 0 {
+//!             throw!("negative value");
+//!         }
+//!     }))
+//! }
+//! ```
+//!
+//! # Throwing async blocks
+//!
+//! Async blocks work the same way as closures: annotate them with `#[throws]` inside a throwing
+//! function, or use the `throws_async!` macro anywhere else. Because there is nowhere to write
+//! the return type of an async block, the error type must be given to the macro, which also makes
+//! sure the error type can be inferred when the block is passed to something like `spawn`.
+//!
+//! ## Example
+//!
+//! ```
+//! use culpa::{throw, throws_async};
+//!
+//! async fn read_config() -> Result<String, std::io::Error> {
+//! #   Ok(String::new())
+//!     // ...
+//! }
+//!
+//! let task = throws_async!(std::io::Error => async move {
+//!     let config = read_config().await?;
+//!     if config.is_empty() {
+//!         throw!(std::io::Error::from(std::io::ErrorKind::InvalidData));
+//!     }
+//!     config.len()
+//! });
+//! ```
+//!
+//! # Try blocks
+//!
+//! The `try_block!` macro evaluates a block the way the body of a throwing function would be:
+//! its final expression is Ok-wrapped, and `?` and `throw!` exit the block with the error rather
+//! than the enclosing function. Like with closures, the error type is given before a `=>`, and
+//! `Option => { ... }` makes a block that evaluates to an `Option`.
+//!
+//! A `return` inside the block still returns from the enclosing function, as it would anywhere
+//! else in the function, so the block must evaluate to its wrapper type rather than the
+//! function's return type. In a throwing function, the value returned is Ok-wrapped like that of
+//! any other `return` in it.
+//!
+//! A `break` or `continue` inside the block for a loop around it must give the label of that
+//! loop, as in `'outer: for ... { try_block!({ ... break 'outer; }) }`; without one it is an error.
+//!
+//! A `?` in the arguments of a standard macro taking expressions, like `vec!` or `format!`, exits
+//! the block too. Other macros cannot be seen into, so a `?` in their arguments is an error rather
+//! than a return from the function, and must be moved out of the macro.
+//!
+//! ## Example
+//!
+//! ```
+//! use culpa::{throw, try_block};
+//!
+//! fn port(config: &str) -> u16 {
+//!     let port = try_block!(std::num::ParseIntError => {
+//!         let port: u16 = config.trim().parse()?;
+//!         port + 1
+//!     });
+//!     port.unwrap_or(8080)
+//! }
+//!
+//! let first = try_block!(Option => {
+//!     let first = [1, 2, 3].first()?;
+//!     if *first == 0 {
+//!         throw!();
+//!     }
+//!     first * 2
+//! });
+//!
+//! assert_eq!(8001, port("8000"));
+//! assert_eq!(8080, port("http"));
+//! assert_eq!(Some(2), first);
+//! ```
+//!
+//! # Async functions in traits
+//!
+//! `#[throws]` works on `async fn` in traits and their impls. A method that is not `async` but
+//! returns `impl Future<Output = T>`, as when a trait needs a `Send` bound on the future, has the
+//! output of its future transformed instead, and its body must end with the async block that
+//! becomes the future. Statements before that block run when the method is called and are not
+//! affected by the attribute.
+//!
+//! A function is a method when it takes `self`, wherever the attribute is applied, so that a trait
+//! and its impls expand to the same signatures. Other functions returning `impl Future`, including
+//! associated functions without `self`, have their return type transformed like any other.
+//!
+//! ## Example
+//!
+//! ```
+//! use std::future::Future;
+//!
+//! use culpa::throws;
+//!
+//! # pub struct Data;
+//! # type Error = std::io::Error;
+//! pub trait Load {
+//!     // Returns `impl Future<Output = Result<Data, Error>> + Send`
+//!     #[throws]
+//!     fn load(&self) -> impl Future<Output = Data> + Send;
+//! }
+//!
+//! struct Disk;
+//!
+//! impl Load for Disk {
+//!     #[throws]
+//!     fn load(&self) -> impl Future<Output = Data> + Send {
+//!         async move {
+//!             // ...
+//!             Data
+//!         }
+//!     }
+//! }
+//! ```
+//!
+//! # Error context
+//!
+//! With the `context` feature enabled, a message describing what was being done can be attached
+//! to an error when it is thrown, by passing the message after the error to `throw!`. The error is
+//! then wrapped in a [`Context`], which displays the message and returns the original error as
+//! its `source`. The [`context!`] macro constructs a `Context` directly, and [`ResultExt`] adds
+//! methods to attach context to a `Result` before using `?` on it.
+//!
+//! Messages are plain string literals, unless the `alloc` feature is enabled too, in which case
+//! they are format strings followed by their arguments.
+//!
+//! ## Example
+//!
+//! ```
+//! # #[cfg(all(feature = "context", feature = "alloc"))] {
+//! use culpa::{throw, throws, Context, ResultExt};
+//!
+//! #[throws(Context<std::io::Error>)]
+//! fn load(path: &str) -> String {
+//!     let config = std::fs::read_to_string(path).context("loading the config")?;
+//!     if config.is_empty() {
+//!         throw!(std::io::Error::from(std::io::ErrorKind::InvalidData), "{} is empty", path);
+//!     }
+//!     config
+//! }
+//! # }
+//! ```
+//!
+//! # Reporting errors from `main`
+//!
+//! With the `std` feature, the `main` attribute makes a function return a [`Report`], which
+//! prints the error followed by each of its sources with `Display` when it is returned from
+//! `main`, instead of the `Debug` output of a `Result`, and exits with code 1 or the code given
+//! as `exit_code`, which cannot be 0 as that would report success. The body of the function
+//! throws like with `throws`, taking the same error type argument, and `?` can be used in it. The
+//! attribute can be used on `#[test]` functions too, so that failing tests print their errors the
+//! same way. `async` functions are supported, for use with attributes like `#[tokio::main]`.
+//!
+//! `Report` also implements [`Succeed`] and [`Throw`], so `#[throws(Error as Report)]` works as
+//! well, with the limits described for other types below.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! # #[cfg(feature = "std")] {
+//! use std::io;
+//!
+//! // Prints `Error: entity not found` and exits with code 2 if the file is missing
+//! #[culpa::main(io::Error, exit_code = 2)]
+//! fn main() {
+//!     let config = std::fs::read_to_string("config.toml")?;
+//!     println!("{}", config);
+//! }
+//! # }
+//! ```
+//!
+//! # Error return traces
+//!
+//! With the `trace` feature, wrapping an error type in [`Traced`] records where errors are thrown
+//! and the path they take back up the stack. Converting an error into a `Traced` error, as
+//! `throw!` and `?` do, records the location of the conversion, and every `?` in a `throws` or
+//! `try_fn` function that the error then propagates through adds its own location. The locations
+//! are stored in a small fixed-size buffer, so this works without `std` or `alloc`.
+//!
+//! With the feature enabled, `?` in throwing functions is rewritten by the macros rather than
+//! being the built-in operator, so it works only on `Result`, `Option` and the `Poll` types
+//! supported by `as Poll` and `as PollStream`.
+//!
+//! ## Example
+//!
+//! ```
+//! # #[cfg(feature = "trace")] {
+//! use culpa::{throw, throws, Traced};
+//!
+//! #[derive(Debug)]
+//! struct NotFound;
+//!
+//! #[throws(Traced<NotFound>)]
+//! fn find(key: &str) -> u32 {
+//!     throw!(NotFound);
+//! }
+//!
+//! #[throws(Traced<NotFound>)]
+//! fn lookup() -> u32 {
+//!     find("key")? + 1
+//! }
+//!
+//! let error = lookup().unwrap_err();
+//! for location in error.trace() {
+//!     println!("at {}", location);
+//! }
+//! # }
+//! ```
+//!
+//! # Renamed and re-exported culpa
+//!
+//! The code generated by the macros refers to culpa by the name of the dependency on it in the
+//! `Cargo.toml` of the crate being compiled, so renaming the dependency works. When culpa is used
+//! through a crate re-exporting it instead, the path to it is given with a `crate = path` argument
+//! after the other arguments, as in `#[throws(E, crate = prelude::culpa)]`,
+//! `#[try_fn(crate = prelude::culpa)]` or `throws_closure!(E, crate = prelude::culpa => ...)`.
+//! Closures and async blocks annotated inside the body use the same path. `#[culpa::main]` takes
+//! the same argument, and an enum deriving `Fallible` gives it in a `#[culpa(crate = path)]`
+//! attribute.
+//!
+//! ## Example
+//!
+//! ```
+//! mod prelude {
+//!     pub mod culpa {
+//!         pub use ::culpa::*;
+//!     }
+//! }
+//!
+//! #[culpa::throws(std::num::ParseIntError, crate = prelude::culpa)]
+//! fn parse(input: &str) -> u8 {
+//!     input.parse()?
+//! }
+//!
+//! assert_eq!(parse("1"), Ok(1));
+//! ```
+//!
 //! # Other `Try` types
 //!
 //! The `?` syntax in Rust is controlled by a trait called `Try`, which is currently unstable.
 //! Because this feature is unstable and I don't want to maintain compatibility if its interface
-//! changes, this crate currently only works with two stable `Try` types: `Result` and `Option`.
-//! However, its designed so that it will hopefully support other `Try` types as well in the
-//! future.
+//! changes, this crate uses its own pair of traits instead: [`Succeed`] is used to Ok-wrap the
+//! returned values, and [`Throw`] is used by the `throw!` macro to return errors. They are
+//! implemented for `Result`, `Option` and `ControlFlow`, and can be implemented for your own types
+//! too, which can then be used with `as` in `throws` or as the return type of a `try_fn`. For
+//! enums the implementations can be derived with `Fallible`, marking the success variant with
+//! `#[culpa(ok)]` and the error variant with `#[culpa(err)]`. The variants must have a single
+//! field, or no fields at all to use `()`. A variant without fields can also be marked with
+//! `#[culpa(empty)]` to implement [`ThrowEmpty`], so that `throw!()` returns it.
+//!
+//! When using `as` the ok and error types are added to the end of the generic arguments of the
+//! wrapper type, so they must be its last two type parameters. Other wrapper types can mark where
+//! the types go with `@ok` and `@err` placeholders instead, as in
+//! `#[throws(E as MyResult<'a, @ok, Context, @err>)]`, which also allows lifetimes, const
+//! generics and fixed types after them. Without an error type before the `as`, the wrapper only
+//! has an `@ok` placeholder.
+//!
+//! Because `Try` itself is unstable, `?` cannot be used inside functions returning these types,
+//! errors must be thrown with `throw!` instead.
+//!
+//! ## Example
+//!
+//! ```
+//! use culpa::{throw, throws, Fallible};
+//!
+//! #[derive(Debug, PartialEq, Fallible)]
+//! enum Outcome<T, E> {
+//!     #[culpa(ok)]
+//!     Done(T),
+//!     #[culpa(err)]
+//!     Failed(E),
+//!     #[culpa(empty)]
+//!     Skipped,
+//! }
+//!
+//! #[throws(String as Outcome)]
+//! fn process(input: &str) -> usize {
+//!     if input.is_empty() {
+//!         throw!();
+//!     }
+//!     match input.parse() {
+//!         Ok(value) => value,
+//!         Err(err) => throw!(format!("invalid input: {}", err)),
+//!     }
+//! }
+//!
+//! assert_eq!(process(""), Outcome::Skipped);
+//! assert_eq!(process("1"), Outcome::Done(1));
+//! assert_eq!(process("a"), Outcome::Failed("invalid input: invalid digit found in string".into()));
+//! ```
+//!
+//! # Implementing futures and streams
+//!
+//! `Try` also has some other stable implementations: specifically `Poll<Result<T, E>>` and
+//! `Poll<Option<Result<T, E>>>`, the return types of `Future::poll` and `Stream::poll_next` when
+//! the output is a `Result`. These are supported by `throws` with `as Poll` and `as PollStream`
+//! (which are shorthands for `as Poll<Result>` and `as Poll<Option<Result>>`).
+//!
+//! Following the `Try` implementation for these types, the body of the function evaluates to
+//! `Poll<T>` or `Poll<Option<T>>` respectively, so you can still return `Poll::Pending`, and only
+//! ready values are Ok-wrapped. `?` can be used on a `Poll<Result<T, E>>` to get a `Poll<T>`, or
+//! combined with `core::task::ready!` to get the `T`. In a stream `throw!()` ends the stream by
+//! returning `Poll::Ready(None)`.
+//!
+//! ## Example
+//!
+//! ```
+//! use std::future::Future;
+//! use std::io;
+//! use std::pin::Pin;
+//! use std::task::{ready, Context, Poll};
+//!
+//! use culpa::throws;
+//!
+//! struct Length<F>(F);
+//!
+//! impl<F: Future<Output = io::Result<String>> + Unpin> Future for Length<F> {
+//!     type Output = io::Result<usize>;
+//!
+//!     #[throws(io::Error as Poll)]
+//!     fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> usize {
+//!         let string = ready!(Pin::new(&mut self.0).poll(cx))?;
+//!         Poll::Ready(string.len())
+//!     }
+//! }
+//! ```
+//!
+//! # Breaking out of traversals
 //!
-//! It's worth noting that `Try` also has some other stable implementations: specifically `Poll`.
-//! Because of the somewhat unusual implementation of `Try` for those types, this crate does not
-//! support `throws` syntax on functions that return `Poll` (so you can't use this syntax when
-//! implementing a `Future` by hand, for example). I hope to come up with a way to support `Poll`
-//! in the future.
+//! `ControlFlow<B, C>` is supported by `throws` with `as ControlFlow`, where the thrown type is
+//! `B`, the type broken out with, and the function returns `C` to continue. `throw!(value)` breaks
+//! with the value converted with `From`, and `?` can be used on other `ControlFlow`s breaking with
+//! the same type. `try_fn` works for functions returning `ControlFlow` written out in full too.
+//!
+//! ## Example
+//!
+//! ```
+//! use std::ops::ControlFlow;
+//!
+//! use culpa::{throw, throws};
+//!
+//! enum Tree {
+//!     Leaf(i32),
+//!     Node(Vec<Tree>),
+//! }
+//!
+//! #[throws(i32 as ControlFlow)]
+//! fn sum(tree: &Tree) -> i32 {
+//!     match tree {
+//!         Tree::Leaf(value) if *value < 0 => throw!(*value),
+//!         Tree::Leaf(value) => *value,
+//!         Tree::Node(children) => {
+//!             let mut total = 0;
+//!             for child in children {
+//!                 total += sum(child)?;
+//!             }
+//!             total
+//!         }
+//!     }
+//! }
+//!
+//! let tree = Tree::Node(vec![Tree::Leaf(1), Tree::Leaf(-2), Tree::Leaf(-3)]);
+//! assert_eq!(sum(&tree), ControlFlow::Break(-2));
+//! ```
+//!
+//! # Debugging the generated code
+//!
+//! Type errors in a transformed function can refer to code generated by the macros, like
+//! `<Result<_, E> as _Succeed>::from_ok(__ret)`. Adding a `debug` argument after the other
+//! arguments, as in `#[throws(E, debug)]`, `#[try_fn(debug)]` or `throws_closure!(E, debug =>
+//! ...)`, shows the generated code in a compile error next to any errors in it. The argument is
+//! only meant to be added while debugging, since the error fails the build.
+//!
+//! The `culpa-macros-core` crate exposes the transformations behind the macros as functions, along
+//! with an `expand` function that shows the code generated for the annotated functions in a source
+//! file, for use by other tools.
+//!
+//! ## Example
+//!
+//! ```compile_fail
+//! # type Error = std::num::ParseIntError;
+//! #[culpa::throws(Error, debug)]
+//! fn parse(input: &str) -> u8 {
+//!     input.parse()?
+//! }
+//! ```
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(feature = "std")]
+extern crate std;
 
 #[doc(inline)]
 /// Annotates a function that "throws" a Result.
@@ -126,34 +719,386 @@ pub use culpa_macros::throws;
 /// See the main crate docs for more details.
 pub use culpa_macros::try_fn;
 
+#[doc(inline)]
+/// Opts a function out of a `throws` or `try_fn` attribute on its impl block, trait or module.
+///
+/// See the main crate docs for more details.
+pub use culpa_macros::nothrow;
+
+#[doc(inline)]
+/// Transforms a closure that "throws" a Result.
+///
+/// See the main crate docs for more details.
+pub use culpa_macros::throws_closure;
+
+#[doc(inline)]
+/// Transforms an async block that "throws" a Result.
+///
+/// See the main crate docs for more details.
+pub use culpa_macros::throws_async;
+
+#[cfg(feature = "std")]
+#[doc(inline)]
+/// Makes `main` return a [`Report`], which prints its error and sets the exit code on failure.
+///
+/// See the main crate docs for more details.
+pub use culpa_macros::main;
+
+#[doc(inline)]
+/// Evaluates a block that "throws" a Result, without leaving the enclosing function.
+///
+/// See the main crate docs for more details.
+pub use culpa_macros::try_block;
+
+#[doc(inline)]
+/// Sets the default error type and the path to culpa for the macros in an inline module.
+///
+/// See the main crate docs for more details.
+pub use culpa_macros::config;
+
 /// Throw an error.
 ///
 /// This macro is equivalent to `Err($err)?`.
+///
+/// With the `alloc` feature, a format string and its arguments can be passed instead of an error,
+/// like `throw!("bad header {}", name)`, to throw the formatted `String` converted into the error
+/// type. A string literal on its own is thrown as a `&'static str` without formatting it.
+///
+/// With the `context` feature, a message can be passed after the error to wrap it in a
+/// [`Context`], like `throw!(error, "loading {}", path)`. See [`context!`] for the syntax.
+///
+/// Without an argument it throws the empty failure state of the return type, such as `None` for
+/// an `Option`, and only compiles for return types implementing [`ThrowEmpty`].
 #[macro_export]
 macro_rules! throw {
-    ($err:expr) => {
-        return <_ as $crate::__internal::_Throw>::from_error((::core::convert::From::from($err)))
+    // Used in try blocks, which are exited with a `break` instead of a `return`
+    (@exit[$($exit:tt)*] $fmt:literal, $($arg:tt)+) => {
+        $crate::throw!(@exit[$($exit)*] $crate::__message!($fmt, $($arg)+))
+    };
+    (@exit[$($exit:tt)*] $err:expr, $($context:tt)+) => {
+        $crate::throw!(@exit[$($exit)*] $crate::context!($err, $($context)+))
+    };
+    (@exit[$($exit:tt)*] $err:expr) => {
+        $($exit)* <_ as $crate::__internal::_Throw>::from_error((::core::convert::From::from($err)))
+    };
+    (@exit[$($exit:tt)*]) => {
+        $($exit)* <_ as $crate::__internal::_ThrowEmpty>::empty()
+    };
+    // Used in functions with an `impl Trait` error type, where the error
+    // cannot be converted because its type is what is being inferred
+    (@exact $fmt:literal, $($arg:tt)+) => {
+        $crate::throw!(@exact $crate::__message!($fmt, $($arg)+))
+    };
+    (@exact $err:expr, $($context:tt)+) => {
+        $crate::throw!(@exact $crate::context!($err, $($context)+))
+    };
+    (@exact $err:expr) => {
+        return <_ as $crate::__internal::_Throw>::from_error($err)
+    };
+    (@exact) => {
+        $crate::throw!()
     };
     () => {
-        return <_ as ::core::default::Default>::default()
+        return <_ as $crate::__internal::_ThrowEmpty>::empty()
+    };
+    ($fmt:literal, $($arg:tt)+) => {
+        $crate::throw!(@exit[return] $fmt, $($arg)+)
+    };
+    ($err:expr, $($context:tt)+) => {
+        $crate::throw!(@exit[return] $err, $($context)+)
+    };
+    ($err:expr) => {
+        $crate::throw!(@exit[return] $err)
+    };
+}
+
+/// Throw an error, the same as [`throw!`].
+///
+/// This is an alias for people used to the name from other error handling libraries, like
+/// `bail!("bad header {}", name)`.
+#[macro_export]
+macro_rules! bail {
+    ($($args:tt)*) => {
+        $crate::throw!($($args)*)
+    };
+}
+
+/// Throw an error if a condition is false.
+///
+/// `ensure!(cond, ...)` is equivalent to `if !cond { throw!(...) }`, taking the same arguments as
+/// [`throw!`] after the condition.
+///
+/// Without an error, like `assert!` the failing condition is converted into the error as a
+/// `&'static str` describing it, such as `"condition failed: len > 0"`. In a function returning
+/// an `Option` this throws `None`.
+#[macro_export]
+macro_rules! ensure {
+    (@exit $exit:tt $cond:expr, $($args:tt)+) => {
+        if !$cond {
+            $crate::throw!(@exit $exit $($args)+);
+        }
+    };
+    (@exit[$($exit:tt)*] $cond:expr $(,)?) => {
+        if !$cond {
+            $($exit)* <_ as $crate::__internal::_Throw>::from_error(
+                $crate::__internal::_Condition::from_condition($crate::__condition!($cond)),
+            );
+        }
+    };
+    (@exact $cond:expr, $($args:tt)+) => {
+        if !$cond {
+            $crate::throw!(@exact $($args)+);
+        }
+    };
+    (@exact $cond:expr $(,)?) => {
+        $crate::ensure!(@exact $cond, $crate::__condition!($cond))
+    };
+    ($cond:expr, $($args:tt)+) => {
+        if !$cond {
+            $crate::throw!($($args)+);
+        }
+    };
+    ($cond:expr $(,)?) => {
+        $crate::ensure!(@exit[return] $cond)
+    };
+}
+
+/// Throw an error if two values are not equal.
+///
+/// `ensure_eq!(left, right, ...)` is equivalent to `ensure!(left == right, ...)`, including
+/// describing the condition when no error is given.
+#[macro_export]
+macro_rules! ensure_eq {
+    (@exit $exit:tt $left:expr, $right:expr $(, $($args:tt)*)?) => {
+        $crate::ensure!(@exit $exit $left == $right $(, $($args)*)?)
+    };
+    (@exact $left:expr, $right:expr $(, $($args:tt)*)?) => {
+        $crate::ensure!(@exact $left == $right $(, $($args)*)?)
+    };
+    ($left:expr, $right:expr $(, $($args:tt)*)?) => {
+        $crate::ensure!($left == $right $(, $($args)*)?)
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __condition {
+    ($cond:expr) => {
+        ::core::concat!("condition failed: ", ::core::stringify!($cond))
+    };
+}
+
+#[cfg(feature = "alloc")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __message {
+    ($($args:tt)+) => {
+        $crate::__internal::format!($($args)+)
+    };
+}
+
+#[cfg(not(feature = "alloc"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __message {
+    ($($args:tt)*) => {
+        ::core::compile_error!("throwing formatted messages requires the `alloc` feature of culpa")
     };
 }
 
+#[cfg(feature = "std")]
+mod report;
+
+#[cfg(feature = "std")]
+pub use report::Report;
+
+#[cfg(feature = "trace")]
+mod trace;
+
+#[cfg(feature = "trace")]
+pub use trace::Traced;
+
+#[cfg(feature = "context")]
+mod context;
+
+#[cfg(feature = "context")]
+pub use context::{Context, ResultExt};
+
+#[cfg(not(feature = "context"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! context {
+    ($($tt:tt)*) => {
+        ::core::compile_error!(
+            "attaching context to errors requires the `context` feature of culpa"
+        )
+    };
+}
+
+#[doc(inline)]
+/// Derives `Succeed`, `Throw` and `ThrowEmpty` for an enum.
+///
+/// See the main crate docs for more details.
+pub use culpa_macros::Fallible;
+
+/// A type that can be constructed from the successful result of a throwing function.
+///
+/// This is used to Ok-wrap the returned values of functions annotated with `throws` or `try_fn`.
+pub trait Succeed {
+    /// The type of the successful value.
+    type Ok;
+
+    /// Wraps a successful value.
+    fn from_ok(ok: Self::Ok) -> Self;
+}
+
+/// A type that can be constructed from an error thrown in a throwing function.
+///
+/// This is used by the `throw!` macro to construct the returned value.
+pub trait Throw {
+    /// The type of the error.
+    type Error;
+
+    /// Wraps an error.
+    fn from_error(error: Self::Error) -> Self;
+}
+
+/// A type with a failure state that carries no error, such as `None`.
+///
+/// This is used by the `throw!()` macro when it is called without an argument.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` has no empty failure state to throw",
+    label = "`throw!()` needs an error for this return type",
+    note = "use `throw!(error)` to throw an error instead"
+)]
+pub trait ThrowEmpty {
+    /// Constructs the empty failure state.
+    fn empty() -> Self;
+}
+
+/// The error type of `Option`, used when throwing in a function returning an `Option`.
+///
+/// `throw!(NoneError)` and `throw!(None)` are both equivalent to `throw!()`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NoneError;
+
+impl core::fmt::Display for NoneError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("a value was `None`")
+    }
+}
+
+impl core::error::Error for NoneError {}
+
+impl From<Option<core::convert::Infallible>> for NoneError {
+    fn from(_: Option<core::convert::Infallible>) -> Self {
+        NoneError
+    }
+}
+
 #[doc(hidden)]
 pub mod __internal {
-    pub trait _Succeed {
-        type Ok;
-        fn from_ok(ok: Self::Ok) -> Self;
+    pub use crate::Succeed as _Succeed;
+    pub use crate::Throw as _Throw;
+    pub use crate::ThrowEmpty as _ThrowEmpty;
+
+    #[cfg(feature = "alloc")]
+    pub use alloc::format;
+
+    #[cfg(feature = "trace")]
+    pub use crate::trace::{_Trace, _TraceNoop, _TraceReturn, _convert};
+
+    // Converts the description of a failed condition into the error thrown by
+    // `ensure!` without an error, which is `None` in a function returning an
+    // `Option`. Other errors are converted from the description.
+    pub trait _Condition {
+        fn from_condition(condition: &'static str) -> Self;
+    }
+
+    impl<E: From<&'static str>> _Condition for E {
+        #[track_caller]
+        fn from_condition(condition: &'static str) -> E {
+            E::from(condition)
+        }
     }
 
-    pub trait _Throw {
+    impl _Condition for crate::NoneError {
+        fn from_condition(_: &'static str) -> crate::NoneError {
+            crate::NoneError
+        }
+    }
+
+    // Splits the operand of a `?` into its value and its unconverted error.
+    pub trait _Branch {
+        type Output;
         type Error;
-        fn from_error(error: Self::Error) -> Self;
+        fn branch(self) -> Result<Self::Output, Self::Error>;
+    }
+
+    // Splits the operand of a `?` in a function returning `Result<Option<T>,
+    // E>` with `as ResultOption` into its value and the value `R` the function
+    // returns instead, `Ok(None)` for a `None` or the converted error.
+    pub trait _BranchOptional<R> {
+        type Output;
+        fn branch_optional(self) -> Result<Self::Output, R>;
     }
 
     mod stable {
+        use core::ops::ControlFlow;
         use core::task::Poll;
 
+        impl<T, E> super::_Branch for Result<T, E> {
+            type Output = T;
+            type Error = E;
+            fn branch(self) -> Result<T, E> {
+                self
+            }
+        }
+
+        impl<T> super::_Branch for Option<T> {
+            type Output = T;
+            type Error = crate::NoneError;
+            fn branch(self) -> Result<T, crate::NoneError> {
+                self.ok_or(crate::NoneError)
+            }
+        }
+
+        impl<T, E> super::_Branch for Poll<Result<T, E>> {
+            type Output = Poll<T>;
+            type Error = E;
+            fn branch(self) -> Result<Poll<T>, E> {
+                match self {
+                    Poll::Ready(Ok(ok)) => Ok(Poll::Ready(ok)),
+                    Poll::Ready(Err(error)) => Err(error),
+                    Poll::Pending => Ok(Poll::Pending),
+                }
+            }
+        }
+
+        impl<T, E> super::_Branch for Poll<Option<Result<T, E>>> {
+            type Output = Poll<Option<T>>;
+            type Error = E;
+            fn branch(self) -> Result<Poll<Option<T>>, E> {
+                match self {
+                    Poll::Ready(Some(Ok(ok))) => Ok(Poll::Ready(Some(ok))),
+                    Poll::Ready(Some(Err(error))) => Err(error),
+                    Poll::Ready(None) => Ok(Poll::Ready(None)),
+                    Poll::Pending => Ok(Poll::Pending),
+                }
+            }
+        }
+
+        impl<B, C> super::_Branch for ControlFlow<B, C> {
+            type Output = C;
+            type Error = B;
+            fn branch(self) -> Result<C, B> {
+                match self {
+                    ControlFlow::Continue(value) => Ok(value),
+                    ControlFlow::Break(value) => Err(value),
+                }
+            }
+        }
+
         impl<T, E> super::_Succeed for Result<T, E> {
             type Ok = T;
             fn from_ok(ok: T) -> Self {
@@ -207,6 +1152,57 @@ pub mod __internal {
             }
         }
 
+        impl<T> super::_ThrowEmpty for Poll<Option<T>> {
+            fn empty() -> Self {
+                Poll::Ready(None)
+            }
+        }
+
+        impl<T, R: super::_ThrowEmpty> super::_BranchOptional<R> for Option<T> {
+            type Output = T;
+            fn branch_optional(self) -> Result<T, R> {
+                self.ok_or_else(R::empty)
+            }
+        }
+
+        impl<T, E, R: super::_Throw> super::_BranchOptional<R> for Result<T, E>
+        where
+            R::Error: From<E>,
+        {
+            type Output = T;
+            #[track_caller]
+            fn branch_optional(self) -> Result<T, R> {
+                // Not `map_err`, so that a `Traced` created by the conversion
+                // records the location of the `?`
+                match self {
+                    Ok(value) => Ok(value),
+                    Err(error) => Err(R::from_error(From::from(error))),
+                }
+            }
+        }
+
+        impl<T, E> super::_ThrowEmpty for Result<Option<T>, E> {
+            fn empty() -> Self {
+                Ok(None)
+            }
+        }
+
+        impl<B, C> super::_Succeed for ControlFlow<B, C> {
+            type Ok = C;
+
+            fn from_ok(ok: Self::Ok) -> Self {
+                ControlFlow::Continue(ok)
+            }
+        }
+
+        impl<B, C> super::_Throw for ControlFlow<B, C> {
+            type Error = B;
+
+            fn from_error(error: Self::Error) -> Self {
+                ControlFlow::Break(error)
+            }
+        }
+
         impl<T> super::_Succeed for Option<T> {
             type Ok = T;
 
@@ -214,6 +1210,20 @@ pub mod __internal {
                 Some(ok)
             }
         }
+
+        impl<T> super::_Throw for Option<T> {
+            type Error = crate::NoneError;
+
+            fn from_error(_: Self::Error) -> Self {
+                None
+            }
+        }
+
+        impl<T> super::_ThrowEmpty for Option<T> {
+            fn empty() -> Self {
+                None
+            }
+        }
     }
 }
 
@@ -0,0 +1,79 @@
+use std::boxed::Box;
+use std::eprintln;
+use std::error::Error;
+use std::num::NonZeroU8;
+use std::process::{ExitCode, Termination};
+
+/// The result of a `main` or test function, which reports a failure by printing the error.
+///
+/// When it is returned from `main` and holds an error, the error is printed with `Display`
+/// followed by each of its sources, and the process exits with the exit code of the report,
+/// which is 1 unless changed with [`Report::with_exit_code`].
+///
+/// This is used by [`main`](crate::main), or directly with `#[throws(Error as culpa::Report)]`.
+pub struct Report<T = (), E = Box<dyn Error>> {
+    result: Result<T, E>,
+    exit_code: NonZeroU8,
+}
+
+impl<T, E> Report<T, E> {
+    /// Wraps the result of a function, to exit with code 1 if it is an error.
+    pub fn new(result: Result<T, E>) -> Report<T, E> {
+        Report::with_exit_code(result, NonZeroU8::MIN)
+    }
+
+    /// Wraps the result of a function, to exit with the given code if it is an error.
+    ///
+    /// The code is not zero, so that an error is never reported as a success.
+    pub fn with_exit_code(result: Result<T, E>, exit_code: NonZeroU8) -> Report<T, E> {
+        Report { result, exit_code }
+    }
+
+    /// The code the process exits with if the result is an error.
+    pub fn exit_code(&self) -> u8 {
+        self.exit_code.get()
+    }
+
+    /// Returns the wrapped result.
+    pub fn into_result(self) -> Result<T, E> {
+        self.result
+    }
+}
+
+impl<T, E> From<Result<T, E>> for Report<T, E> {
+    fn from(result: Result<T, E>) -> Report<T, E> {
+        Report::new(result)
+    }
+}
+
+impl<T: Termination, E: Into<Box<dyn Error>>> Termination for Report<T, E> {
+    fn report(self) -> ExitCode {
+        let error = match self.result {
+            Ok(ok) => return ok.report(),
+            Err(error) => error.into(),
+        };
+        eprintln!("Error: {}", error);
+        let mut source = error.source();
+        while let Some(error) = source {
+            eprintln!("Caused by: {}", error);
+            source = error.source();
+        }
+        ExitCode::from(self.exit_code.get())
+    }
+}
+
+impl<T, E> crate::Succeed for Report<T, E> {
+    type Ok = T;
+
+    fn from_ok(ok: T) -> Self {
+        Report::new(Ok(ok))
+    }
+}
+
+impl<T, E> crate::Throw for Report<T, E> {
+    type Error = E;
+
+    fn from_error(error: E) -> Self {
+        Report::new(Err(error))
+    }
+}
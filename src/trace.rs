@@ -0,0 +1,198 @@
+use core::fmt;
+use core::panic::Location;
+
+/// An error with a trace of the locations it was thrown from and propagated through.
+///
+/// A `Traced` error is created by converting an error into it, which records the location of the
+/// conversion: the `throw!` or `?` that threw the error. Each `?` in a `throws` or `try_fn`
+/// function that the error then propagates through adds its own location, giving a trace from
+/// where the error was thrown to where it was handled, like a stack trace but only through the
+/// functions that returned the error.
+///
+/// The trace is stored inline with room for [`Traced::CAPACITY`] locations, any more are counted
+/// but not recorded. The capacity is kept small so that results with a `Traced` error stay small.
+pub struct Traced<E> {
+    error: E,
+    locations: [&'static Location<'static>; CAPACITY],
+    len: usize,
+    truncated: usize,
+}
+
+const CAPACITY: usize = 8;
+
+impl<E> Traced<E> {
+    /// The number of locations that can be recorded in a trace.
+    pub const CAPACITY: usize = CAPACITY;
+
+    /// Wraps an error, recording the location this is called from.
+    #[track_caller]
+    pub fn new(error: E) -> Traced<E> {
+        Traced {
+            error,
+            locations: [Location::caller(); CAPACITY],
+            len: 1,
+            truncated: 0,
+        }
+    }
+
+    /// Adds a location to the trace, unless it is the same as the last location.
+    pub fn push(&mut self, location: &'static Location<'static>) {
+        if *self.locations[self.len - 1] == *location {
+            return;
+        }
+        match self.locations.get_mut(self.len) {
+            Some(slot) => {
+                *slot = location;
+                self.len += 1;
+            }
+            None => self.truncated += 1,
+        }
+    }
+
+    /// The locations the error was thrown from and propagated through, starting where it was
+    /// thrown.
+    pub fn trace(&self) -> impl Iterator<Item = &'static Location<'static>> + '_ {
+        self.locations[..self.len].iter().copied()
+    }
+
+    /// The number of locations that did not fit in the trace.
+    pub fn truncated(&self) -> usize {
+        self.truncated
+    }
+
+    /// The error that was thrown.
+    pub fn error(&self) -> &E {
+        &self.error
+    }
+
+    /// Discards the trace, returning the error.
+    pub fn into_error(self) -> E {
+        self.error
+    }
+}
+
+impl<E> From<E> for Traced<E> {
+    #[track_caller]
+    fn from(error: E) -> Traced<E> {
+        Traced::new(error)
+    }
+}
+
+impl<E: fmt::Display> fmt::Display for Traced<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.error.fmt(f)
+    }
+}
+
+impl<E: fmt::Debug> fmt::Debug for Traced<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        struct Trace<'a, E>(&'a Traced<E>);
+
+        impl<E> fmt::Debug for Trace<'_, E> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                let mut list = f.debug_list();
+                for location in self.0.trace() {
+                    list.entry(&format_args!("{}", location));
+                }
+                list.finish()
+            }
+        }
+
+        f.debug_struct("Traced")
+            .field("error", &self.error)
+            .field("trace", &Trace(self))
+            .finish()
+    }
+}
+
+impl<E: core::error::Error> core::error::Error for Traced<E> {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        self.error.source()
+    }
+}
+
+// Adds the location of a `?` to the trace of the error it throws, when the
+// error is `Traced`, using autoref specialization: `_TraceReturn` is
+// implemented for `_Trace` itself and so is found first when the thrown value
+// has a traced error, while `_TraceNoop` is implemented for any reference to a
+// `_Trace`. The thrown value is used rather than the error itself because its
+// type is known before the error is converted into it.
+pub struct _Trace<T>(core::cell::Cell<Option<T>>);
+
+impl<T> _Trace<T> {
+    pub fn new(thrown: T) -> _Trace<T> {
+        _Trace(core::cell::Cell::new(Some(thrown)))
+    }
+
+    fn take(&self) -> T {
+        self.0.take().unwrap()
+    }
+}
+
+pub trait _TraceReturn<T> {
+    #[track_caller]
+    fn __culpa_trace(&self) -> T;
+}
+
+impl<T: _Traced> _TraceReturn<T> for _Trace<T> {
+    #[track_caller]
+    fn __culpa_trace(&self) -> T {
+        let mut thrown = self.take();
+        thrown.push(Location::caller());
+        thrown
+    }
+}
+
+pub trait _TraceNoop<T> {
+    fn __culpa_trace(&self) -> T;
+}
+
+impl<T> _TraceNoop<T> for &_Trace<T> {
+    fn __culpa_trace(&self) -> T {
+        self.take()
+    }
+}
+
+// A thrown value with a traced error.
+pub trait _Traced {
+    fn push(&mut self, location: &'static Location<'static>);
+}
+
+impl<T, E> _Traced for Result<T, Traced<E>> {
+    fn push(&mut self, location: &'static Location<'static>) {
+        if let Err(error) = self {
+            error.push(location);
+        }
+    }
+}
+
+impl<T, E> _Traced for core::task::Poll<Result<T, Traced<E>>> {
+    fn push(&mut self, location: &'static Location<'static>) {
+        if let core::task::Poll::Ready(Err(error)) = self {
+            error.push(location);
+        }
+    }
+}
+
+impl<T, E> _Traced for core::task::Poll<Option<Result<T, Traced<E>>>> {
+    fn push(&mut self, location: &'static Location<'static>) {
+        if let core::task::Poll::Ready(Some(Err(error))) = self {
+            error.push(location);
+        }
+    }
+}
+
+impl<C, B> _Traced for core::ops::ControlFlow<Traced<B>, C> {
+    fn push(&mut self, location: &'static Location<'static>) {
+        if let core::ops::ControlFlow::Break(error) = self {
+            error.push(location);
+        }
+    }
+}
+
+// Converts the error of a `?`, so that a `Traced` created by the conversion
+// records the location of the `?`.
+#[track_caller]
+pub fn _convert<E, F: From<E>>(error: E) -> F {
+    From::from(error)
+}
@@ -0,0 +1,79 @@
+mod common;
+
+use std::future::Future;
+
+use culpa::{throw, throws, throws_async};
+
+use common::block_on;
+
+type Error = isize;
+
+async fn fallible(x: i32) -> Result<i32, i32> {
+    if x < 0 {
+        Err(x)
+    } else {
+        Ok(x)
+    }
+}
+
+#[throws(_)]
+pub fn annotated_async_block() -> impl Future<Output = Result<i32, i32>> {
+    #[throws(i32)]
+    async move {
+        fallible(1).await?
+    }
+}
+
+#[throws(_)]
+pub async fn async_block_in_async_fn() -> i32 {
+    let task = #[throws(i32)]
+    async {
+        if true {
+            return 1;
+        }
+        throw!(2);
+    };
+    task.await.unwrap()
+}
+
+#[test]
+fn test_annotated_async_block() {
+    assert_eq!(Ok(1), block_on(annotated_async_block().unwrap()));
+}
+
+#[test]
+fn test_async_block_in_async_fn() {
+    assert_eq!(Ok(1), block_on(async_block_in_async_fn()));
+}
+
+#[test]
+fn test_throws_async_macro() {
+    let task = |x| {
+        throws_async!(i32 => async move {
+            let x = fallible(x).await?;
+            if x == 0 {
+                throw!(0);
+            }
+            x
+        })
+    };
+    assert_eq!(Ok(1), block_on(task(1)));
+    assert_eq!(Err(0), block_on(task(0)));
+    assert_eq!(Err(-1), block_on(task(-1)));
+}
+
+#[test]
+fn test_throws_async_macro_unit() {
+    let task = throws_async!(i32 => async {
+        if false {
+            return;
+        }
+    });
+    assert_eq!(Ok(()), block_on(task));
+}
+
+#[test]
+fn test_throws_async_macro_as_option() {
+    let task = throws_async!(as Option => async { fallible(-1).await.ok()? });
+    assert_eq!(None, block_on(task));
+}
@@ -0,0 +1,107 @@
+use culpa::{nothrow, throw, throws, try_fn};
+
+#[derive(Debug, PartialEq)]
+pub struct StoreError;
+
+pub struct Store(Vec<u32>);
+
+#[throws(StoreError)]
+impl Store {
+    pub fn get(&self, index: usize) -> u32 {
+        match self.0.get(index) {
+            Some(value) => *value,
+            None => throw!(StoreError),
+        }
+    }
+
+    pub fn first(&self) -> u32 {
+        if self.0.is_empty() {
+            throw!(StoreError);
+        }
+        return self.get(0)?;
+    }
+
+    #[nothrow]
+    pub fn count(&self) -> usize {
+        self.0.len()
+    }
+
+    #[throws(as Option)]
+    pub fn last(&self) -> u32 {
+        *self.0.last()?
+    }
+
+    pub const EMPTY: Store = Store(Vec::new());
+}
+
+#[throws(StoreError)]
+pub trait Lookup {
+    fn lookup(&self, index: usize) -> u32;
+
+    fn lookup_twice(&self, index: usize) -> u32 {
+        self.lookup(index)? * 2
+    }
+
+    #[nothrow]
+    fn name(&self) -> &'static str {
+        "lookup"
+    }
+}
+
+#[throws(StoreError)]
+impl Lookup for Store {
+    fn lookup(&self, index: usize) -> u32 {
+        self.get(index)?
+    }
+}
+
+#[try_fn]
+pub mod parsing {
+    use culpa::{nothrow, throw};
+
+    pub fn parse(input: &str) -> Result<u8, std::num::ParseIntError> {
+        input.parse()?
+    }
+
+    pub fn non_zero(input: u8) -> Option<u8> {
+        if input == 0 {
+            throw!();
+        }
+        input
+    }
+
+    #[nothrow]
+    pub fn is_zero(input: u8) -> bool {
+        input == 0
+    }
+
+    pub struct NotAFunction;
+}
+
+#[test]
+fn test_impl_block() {
+    let store = Store(vec![1, 2]);
+    assert_eq!(Ok(2), store.get(1));
+    assert_eq!(Err(StoreError), store.get(2));
+    assert_eq!(Ok(1), store.first());
+    assert_eq!(Err(StoreError), Store::EMPTY.first());
+    assert_eq!(2, store.count());
+    assert_eq!(Some(2), store.last());
+}
+
+#[test]
+fn test_trait() {
+    let store = Store(vec![1, 2]);
+    assert_eq!(Ok(4), store.lookup_twice(1));
+    assert_eq!(Err(StoreError), store.lookup_twice(2));
+    assert_eq!("lookup", store.name());
+}
+
+#[test]
+fn test_module() {
+    assert_eq!(Ok(1), parsing::parse("1"));
+    assert!(parsing::parse("a").is_err());
+    assert_eq!(None, parsing::non_zero(0));
+    assert!(parsing::is_zero(0));
+    let _ = parsing::NotAFunction;
+}
@@ -0,0 +1,114 @@
+use culpa::{throw, throws, throws_closure, try_fn};
+
+type Error = isize;
+
+#[throws(_)]
+pub fn annotated_closure() -> i32 {
+    let f = #[throws(i32)]
+    |x: i32| {
+        if x < 0 {
+            throw!(x);
+        }
+        x
+    };
+    f(1).unwrap()
+}
+
+#[throws(_)]
+pub fn closure_with_return_type() {
+    let f = #[throws]
+    || -> u8 {
+        if true {
+            return 0;
+        }
+        1
+    };
+    let _: Result<u8, Error> = f();
+}
+
+#[throws(_)]
+pub fn try_fn_closure() {
+    let f = #[try_fn]
+    || -> Option<u8> { 0 };
+    let _: Option<u8> = f();
+}
+
+#[throws(_)]
+pub fn nested_return_in_closure() -> i32 {
+    let f = #[throws(())]
+    || {
+        let g = || 0;
+        return g();
+    };
+    if f().is_err() {
+        return 0;
+    }
+    1
+}
+
+#[try_fn]
+pub fn closure_in_try_fn() -> Result<(), Error> {
+    let f = #[throws(as Option)]
+    |x: u8| x.checked_add(1)?;
+    let _: Option<u8> = f(0);
+}
+
+#[throws(_)]
+pub fn nested_throwing_closures() {
+    let f = #[throws(())]
+    || {
+        let g = #[throws(())]
+        || 0;
+        g()?
+    };
+    let _: Result<i32, ()> = f();
+}
+
+#[test]
+fn test_annotated_closure() {
+    assert_eq!(Ok(1), annotated_closure());
+}
+
+#[test]
+fn test_nested_return_in_closure() {
+    assert_eq!(Ok(1), nested_return_in_closure());
+}
+
+#[test]
+fn test_throws_closure_macro() {
+    let f = throws_closure!(i32 => |x: i32| {
+        if x < 0 {
+            throw!(x);
+        }
+        x
+    });
+    assert_eq!(Ok(1), f(1));
+    assert_eq!(Err(-1), f(-1));
+}
+
+#[test]
+fn test_throws_closure_macro_as_option() {
+    let f = throws_closure!(as Option => move |x: u8| x.checked_add(1)?);
+    assert_eq!(Some(1), f(0));
+    assert_eq!(None, f(u8::MAX));
+}
+
+#[test]
+fn test_throws_closure_macro_default_error() {
+    let f = throws_closure!(|| {
+        throw!(1 as Error);
+    });
+    let result: Result<(), Error> = f();
+    assert_eq!(Err(1), result);
+}
+
+#[test]
+fn test_throws_closure_in_iterator() {
+    let values = [1, 2, -3, 4];
+    let result = values.iter().try_for_each(throws_closure!(i32 => |&value| {
+        if value < 0 {
+            throw!(value);
+        }
+    }));
+    assert_eq!(Err(-3), result);
+}
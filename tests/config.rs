@@ -0,0 +1,158 @@
+#[culpa::config(error = ParseError)]
+mod parsing {
+    // Would be the default error type without the config
+    #[allow(unused_imports)]
+    use std::error::Error;
+
+    use culpa::{throw, throws, throws_closure, try_block};
+
+    #[derive(Debug, PartialEq)]
+    pub struct ParseError;
+
+    impl From<std::num::ParseIntError> for ParseError {
+        fn from(_: std::num::ParseIntError) -> ParseError {
+            ParseError
+        }
+    }
+
+    #[throws]
+    pub fn parse(input: &str) -> u8 {
+        input.parse()?
+    }
+
+    #[throws(_)]
+    pub fn positive(input: &str) -> u8 {
+        let value = parse(input)?;
+        if value == 0 {
+            throw!(ParseError);
+        }
+        value
+    }
+
+    #[throws(std::fmt::Error)]
+    pub fn explicit() {}
+
+    #[throws(as Option)]
+    pub fn first(values: &[u8]) -> u8 {
+        values.first().copied()?
+    }
+
+    pub struct Parser;
+
+    #[throws]
+    impl Parser {
+        pub fn parse(&self, input: &str) -> u8 {
+            input.parse()?
+        }
+    }
+
+    pub fn parse_all(inputs: &[&str]) -> Result<Vec<u8>, ParseError> {
+        let parse = throws_closure!(|input: &&str| -> u8 { input.parse()? });
+        inputs.iter().map(parse).collect()
+    }
+
+    pub fn sum(inputs: &[&str]) -> Result<u32, ParseError> {
+        try_block!({
+            let mut total = 0;
+            for input in inputs {
+                total += u32::from(parse(input)?);
+            }
+            total
+        })
+    }
+
+    pub fn first_plus_one(values: &[u8]) -> Option<u8> {
+        try_block!(Option, crate = ::culpa => { values.first()? + 1 })
+    }
+
+    // The error type is named where the macros are, so it must be in scope
+    pub mod nested {
+        use culpa::throws;
+
+        use super::ParseError;
+
+        #[throws]
+        pub fn parse(input: &str) -> i8 {
+            input.parse()?
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[culpa::main(exit_code = 2)]
+    pub fn main() -> u8 {
+        parse("a")?
+    }
+
+    #[culpa::config(error = std::num::ParseIntError)]
+    pub mod overridden {
+        use culpa::throws;
+
+        #[throws]
+        pub fn parse(input: &str) -> i8 {
+            input.parse()?
+        }
+    }
+}
+
+// A macro with the same name as one of culpa's is left alone
+#[culpa::config(error = ParseError)]
+mod unrelated {
+    macro_rules! try_block {
+        ($body:block) => {
+            $body
+        };
+    }
+
+    pub fn answer() -> u8 {
+        try_block!({ 42 })
+    }
+}
+
+use parsing::{ParseError, Parser};
+
+#[test]
+fn test_default_error() {
+    assert_eq!(parsing::parse("1"), Ok(1));
+    assert_eq!(parsing::parse("a"), Err(ParseError));
+    assert_eq!(parsing::positive("0"), Err(ParseError));
+    assert_eq!(parsing::explicit(), Ok(()));
+    assert_eq!(parsing::first(&[]), None);
+}
+
+#[test]
+fn test_impl_block() {
+    assert_eq!(Parser.parse("2"), Ok(2));
+    assert_eq!(Parser.parse("a"), Err(ParseError));
+}
+
+#[test]
+fn test_closure_and_try_block() {
+    assert_eq!(parsing::parse_all(&["1", "2"]), Ok(vec![1, 2]));
+    assert_eq!(parsing::parse_all(&["1", "a"]), Err(ParseError));
+    assert_eq!(parsing::sum(&["1", "2"]), Ok(3));
+    assert_eq!(parsing::sum(&["a"]), Err(ParseError));
+    assert_eq!(parsing::first_plus_one(&[1]), Some(2));
+    assert_eq!(parsing::first_plus_one(&[]), None);
+}
+
+#[test]
+fn test_nested_modules() {
+    assert_eq!(parsing::nested::parse("a"), Err(ParseError));
+    assert!(parsing::overridden::parse("a")
+        .unwrap_err()
+        .to_string()
+        .contains("invalid digit"));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_main() {
+    let report = parsing::main();
+    assert_eq!(report.exit_code(), 2);
+    assert!(matches!(report.into_result(), Err(ParseError)));
+}
+
+#[test]
+fn test_unrelated_macro() {
+    assert_eq!(unrelated::answer(), 42);
+}
@@ -0,0 +1,83 @@
+#![cfg(feature = "context")]
+
+use std::error::Error as _;
+use std::io;
+
+use culpa::{context, throw, throws, Context, ResultExt};
+
+fn invalid() -> io::Error {
+    io::Error::from(io::ErrorKind::InvalidData)
+}
+
+#[throws(Context<io::Error>)]
+fn throw_with_context(fail: bool) {
+    if fail {
+        throw!(invalid(), "checking the input");
+    }
+}
+
+#[throws(Context<io::Error>)]
+fn question_mark_with_context(result: io::Result<u8>) -> u8 {
+    result.context("reading a byte")?
+}
+
+#[test]
+fn test_throw_with_context() {
+    assert!(throw_with_context(false).is_ok());
+    let error = throw_with_context(true).unwrap_err();
+    assert_eq!("checking the input", error.to_string());
+    assert_eq!(io::ErrorKind::InvalidData, error.error().kind());
+}
+
+#[test]
+fn test_result_context() {
+    assert_eq!(1, question_mark_with_context(Ok(1)).unwrap());
+    let error = question_mark_with_context(Err(invalid())).unwrap_err();
+    assert_eq!("reading a byte", error.message());
+    assert_eq!(io::ErrorKind::InvalidData, error.into_error().kind());
+}
+
+#[test]
+fn test_context_source() {
+    let error = context!(invalid(), "parsing");
+    assert_eq!(invalid().to_string(), error.source().unwrap().to_string());
+}
+
+#[test]
+fn test_context_debug() {
+    let error = context!(1, "counting");
+    assert_eq!(
+        r#"Context { message: "counting", error: 1 }"#,
+        format!("{:?}", error)
+    );
+}
+
+#[cfg(feature = "alloc")]
+mod alloc {
+    use super::*;
+
+    #[throws(Context<io::Error>)]
+    fn throw_formatted(name: &str) {
+        throw!(invalid(), "checking {}", name);
+    }
+
+    #[test]
+    fn test_throw_formatted() {
+        let error = throw_formatted("the input").unwrap_err();
+        assert_eq!("checking the input", error.to_string());
+    }
+
+    #[test]
+    fn test_inline_arguments() {
+        let name = "the input";
+        let error = context!(invalid(), "checking {name}");
+        assert_eq!("checking the input", error.message());
+    }
+
+    #[test]
+    fn test_with_context() {
+        let result: Result<(), Context<io::Error>> =
+            Err(invalid()).with_context(|| format!("checking {}", 1));
+        assert_eq!("checking 1", result.unwrap_err().message());
+    }
+}
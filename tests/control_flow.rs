@@ -0,0 +1,106 @@
+use std::ops::ControlFlow;
+
+use culpa::{throw, throws, throws_closure, try_block, try_fn};
+
+enum Tree {
+    Leaf(i32),
+    Node(Vec<Tree>),
+}
+
+// Sums the leaves of a tree, breaking out with the first negative one
+#[throws(i32 as ControlFlow)]
+fn sum(tree: &Tree) -> i32 {
+    match tree {
+        Tree::Leaf(value) if *value < 0 => throw!(*value),
+        Tree::Leaf(value) => *value,
+        Tree::Node(children) => {
+            let mut total = 0;
+            for child in children {
+                total += sum(child)?;
+            }
+            total
+        }
+    }
+}
+
+fn tree(values: &[i32]) -> Tree {
+    let leaves = values.iter().map(|value| Tree::Leaf(*value)).collect();
+    Tree::Node(vec![Tree::Leaf(1), Tree::Node(leaves)])
+}
+
+#[test]
+fn test_continue() {
+    assert_eq!(sum(&tree(&[2, 3])), ControlFlow::Continue(6));
+}
+
+#[test]
+fn test_break() {
+    assert_eq!(sum(&tree(&[2, -3, -4])), ControlFlow::Break(-3));
+}
+
+#[throws(i64 as ControlFlow)]
+fn find_zero(values: &[u8]) {
+    for (index, value) in values.iter().enumerate() {
+        if *value == 0 {
+            throw!(index as u32);
+        }
+    }
+}
+
+#[test]
+fn test_unit_and_conversion() {
+    assert_eq!(find_zero(&[1, 2]), ControlFlow::Continue(()));
+    assert_eq!(find_zero(&[1, 0, 0]), ControlFlow::Break(1));
+}
+
+#[try_fn]
+fn first_large(values: &[u8]) -> ControlFlow<u8> {
+    for value in values {
+        if *value > 9 {
+            throw!(*value);
+        }
+    }
+}
+
+#[try_fn]
+fn divide(value: u8) -> ControlFlow<&'static str, u8> {
+    if value == 0 {
+        throw!("zero");
+    }
+    100 / value
+}
+
+#[test]
+fn test_try_fn() {
+    assert_eq!(first_large(&[1, 3]), ControlFlow::Continue(()));
+    assert_eq!(first_large(&[1, 40, 60]), ControlFlow::Break(40));
+    assert_eq!(divide(4), ControlFlow::Continue(25));
+    assert_eq!(divide(0), ControlFlow::Break("zero"));
+}
+
+#[test]
+fn test_closure() {
+    let values = [1, 2, -3, 4];
+    let found = values
+        .iter()
+        .try_for_each(throws_closure!(i32 as ControlFlow => |value: &i32| {
+            if *value < 0 {
+                throw!(*value);
+            }
+        }));
+    assert_eq!(found, ControlFlow::Break(-3));
+}
+
+#[test]
+fn test_try_block() {
+    let values = [1, 2, 3];
+    let flow = try_block!(usize as ControlFlow => {
+        for (index, value) in values.iter().enumerate() {
+            if *value > 1 {
+                throw!(index);
+            }
+        }
+        values.len()
+    });
+    assert_eq!(flow, ControlFlow::Break(1));
+}
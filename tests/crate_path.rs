@@ -0,0 +1,114 @@
+use std::num::ParseIntError;
+
+use culpa::{throw, throws_closure, try_block};
+
+// A facade crate re-exporting culpa under another name
+mod errors {
+    pub use culpa as reexported;
+    pub use culpa::{throws, try_fn, Fallible};
+
+    #[cfg(feature = "std")]
+    pub use culpa::main;
+}
+
+use errors::{throws, try_fn};
+
+type Error = ParseIntError;
+
+#[throws(i32, crate = errors::reexported)]
+fn positive(value: i32) -> i32 {
+    if value < 0 {
+        throw!(value);
+    }
+    value
+}
+
+#[throws(crate = crate::errors::reexported)]
+fn parse(input: &str) -> u8 {
+    input.parse()?
+}
+
+#[try_fn(crate = errors::reexported)]
+fn parse_all(inputs: &[&str]) -> Result<Vec<u8>, ParseIntError> {
+    let parse = #[throws(ParseIntError)]
+    |input: &str| -> u8 { input.parse()? };
+    inputs
+        .iter()
+        .map(|input| parse(input))
+        .collect::<Result<_, _>>()?
+}
+
+#[derive(Debug, PartialEq, errors::Fallible)]
+#[culpa(crate = errors::reexported)]
+enum Status {
+    #[culpa(ok)]
+    Running,
+    #[culpa(err)]
+    Stopped(i32),
+}
+
+#[try_fn(crate = errors::reexported)]
+fn status(code: i32) -> Status {
+    if code != 0 {
+        throw!(code);
+    }
+}
+
+#[cfg(feature = "std")]
+#[errors::main(ParseIntError, crate = errors::reexported, exit_code = 2)]
+fn main_parse() -> u8 {
+    "a".parse()?
+}
+
+struct Parser;
+
+#[throws(ParseIntError, crate = errors::reexported)]
+impl Parser {
+    fn parse(&self, input: &str) -> u8 {
+        input.parse()?
+    }
+}
+
+#[test]
+fn test_crate_path() {
+    assert_eq!(positive(1), Ok(1));
+    assert_eq!(positive(-1), Err(-1));
+    assert_eq!(parse("1"), Ok(1));
+    assert!(parse("a").is_err());
+    assert_eq!(parse_all(&["1", "2"]), Ok(vec![1, 2]));
+    assert!(parse_all(&["1", "a"]).is_err());
+    assert_eq!(Parser.parse("3"), Ok(3));
+}
+
+#[test]
+fn test_closure() {
+    let closure = throws_closure!(i32, crate = errors::reexported => |value: i32| {
+        if value < 0 {
+            throw!(value);
+        }
+        value
+    });
+    assert_eq!(closure(1), Ok(1));
+    assert_eq!(closure(-1), Err(-1));
+}
+
+#[test]
+fn test_derive() {
+    assert_eq!(status(0), Status::Running);
+    assert_eq!(status(1), Status::Stopped(1));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_main() {
+    assert_eq!(main_parse().exit_code(), 2);
+}
+
+#[test]
+fn test_option_try_block() {
+    let values = [1, 2];
+    let first = try_block!(Option, crate = errors::reexported => { values.first()? + 1 });
+    assert_eq!(first, Some(2));
+    let third = try_block!(Option, crate = errors::reexported => { values.get(2)? + 1 });
+    assert_eq!(third, None);
+}
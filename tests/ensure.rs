@@ -0,0 +1,117 @@
+use std::task::Poll;
+
+use culpa::{ensure, ensure_eq, throws, try_block, try_fn, Fallible};
+
+#[derive(Debug, PartialEq)]
+pub struct Error(&'static str);
+
+impl From<&'static str> for Error {
+    fn from(message: &'static str) -> Self {
+        Error(message)
+    }
+}
+
+#[derive(Debug, PartialEq, Fallible)]
+pub enum Outcome<T, E> {
+    #[culpa(ok)]
+    Done(T),
+    #[culpa(err)]
+    Failed(E),
+}
+
+#[throws]
+fn positive(value: i32) -> i32 {
+    ensure!(value > 0);
+    value
+}
+
+#[throws]
+fn positive_with_error(value: i32) -> i32 {
+    ensure!(value > 0, Error("not positive"));
+    value
+}
+
+#[throws(as Option)]
+fn positive_option(value: i32) -> i32 {
+    ensure!(value > 0);
+    value
+}
+
+#[try_fn]
+fn positive_poll(value: i32) -> Poll<Result<i32, Error>> {
+    ensure!(value > 0);
+    Poll::Ready(value)
+}
+
+#[try_fn]
+fn positive_outcome(value: i32) -> Outcome<i32, Error> {
+    ensure!(value > 0);
+    value
+}
+
+#[throws]
+fn equal(left: i32, right: i32) {
+    ensure_eq!(left, right);
+}
+
+#[throws]
+fn equal_with_error(left: i32, right: i32) {
+    ensure_eq!(left, right, Error("not equal"));
+}
+
+#[throws(as Option)]
+fn equal_option(left: i32, right: i32) {
+    ensure_eq!(left + 1, right);
+}
+
+#[throws(impl std::fmt::Debug)]
+fn equal_exact(left: i32, right: i32) {
+    ensure_eq!(left, right);
+}
+
+#[test]
+fn test_ensure() {
+    assert_eq!(Ok(1), positive(1));
+    assert_eq!(Err(Error("condition failed: value > 0")), positive(0));
+    assert_eq!(Err(Error("not positive")), positive_with_error(0));
+    assert_eq!(Some(1), positive_option(1));
+    assert_eq!(None, positive_option(0));
+    assert_eq!(Poll::Ready(Ok(1)), positive_poll(1));
+    assert_eq!(
+        Poll::Ready(Err(Error("condition failed: value > 0"))),
+        positive_poll(0)
+    );
+    assert_eq!(Outcome::Done(1), positive_outcome(1));
+    assert_eq!(
+        Outcome::Failed(Error("condition failed: value > 0")),
+        positive_outcome(0)
+    );
+}
+
+#[test]
+fn test_ensure_eq() {
+    assert_eq!(Ok(()), equal(1, 1));
+    assert_eq!(Err(Error("condition failed: left == right")), equal(1, 2));
+    assert_eq!(Err(Error("not equal")), equal_with_error(1, 2));
+    assert_eq!(Some(()), equal_option(1, 2));
+    assert_eq!(None, equal_option(1, 1));
+    assert_eq!(
+        "\"condition failed: left == right\"",
+        format!("{:?}", equal_exact(1, 2).unwrap_err())
+    );
+}
+
+#[test]
+fn test_ensure_in_try_block() {
+    let result: Result<i32, Error> = try_block!(Error => {
+        ensure!(false);
+        1
+    });
+    assert_eq!(Err(Error("condition failed: false")), result);
+
+    let result: Option<i32> = try_block!(Option => {
+        ensure_eq!(1, 2);
+        1
+    });
+    assert_eq!(None, result);
+}
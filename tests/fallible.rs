@@ -0,0 +1,86 @@
+use culpa::{throw, throws, try_fn, Fallible, Succeed, Throw};
+
+#[derive(Debug, PartialEq, Fallible)]
+pub enum Outcome<T, E> {
+    #[culpa(ok)]
+    Done(T),
+    #[culpa(err)]
+    Failed(E),
+    #[culpa(empty)]
+    Skipped,
+}
+
+#[derive(Debug, PartialEq, Fallible)]
+pub enum Status {
+    #[culpa(ok)]
+    Running,
+    #[culpa(err)]
+    Stopped { code: i32 },
+}
+
+#[derive(Debug, PartialEq, Fallible)]
+pub enum OnlyOk<T> {
+    #[culpa(ok)]
+    Value(T),
+    #[allow(dead_code)]
+    Nothing,
+}
+
+#[throws(i32 as Outcome)]
+fn throws_outcome(x: i32) -> i32 {
+    if x < 0 {
+        throw!(x);
+    } else if x == 0 {
+        return 0;
+    } else if x > 100 {
+        throw!();
+    }
+    x * 2
+}
+
+#[try_fn]
+fn try_fn_outcome(x: i32) -> Outcome<i32, &'static str> {
+    if x < 0 {
+        throw!("negative");
+    }
+    x
+}
+
+#[try_fn]
+fn try_fn_status(code: i32) -> Status {
+    if code != 0 {
+        throw!(code);
+    }
+}
+
+#[test]
+fn test_throws_outcome() {
+    assert_eq!(Outcome::Done(0), throws_outcome(0));
+    assert_eq!(Outcome::Done(2), throws_outcome(1));
+    assert_eq!(Outcome::Failed(-1), throws_outcome(-1));
+    assert_eq!(Outcome::Skipped, throws_outcome(101));
+}
+
+#[test]
+fn test_try_fn_outcome() {
+    assert_eq!(Outcome::Done(1), try_fn_outcome(1));
+    assert_eq!(Outcome::Failed("negative"), try_fn_outcome(-1));
+}
+
+#[test]
+fn test_named_and_unit_variants() {
+    assert_eq!(Status::Running, try_fn_status(0));
+    assert_eq!(Status::Stopped { code: 1 }, try_fn_status(1));
+}
+
+#[test]
+fn test_only_ok() {
+    assert_eq!(OnlyOk::Value(1), OnlyOk::from_ok(1));
+}
+
+#[test]
+fn test_traits_on_std_types() {
+    assert_eq!(Ok::<i32, ()>(1), Result::from_ok(1));
+    assert_eq!(Err::<(), i32>(1), Result::from_error(1));
+    assert_eq!(Some(1), Option::from_ok(1));
+}
@@ -0,0 +1,176 @@
+mod common;
+
+use std::fmt::Debug;
+use std::task::Poll;
+
+use culpa::{throw, throws, try_fn};
+
+use common::block_on;
+
+#[derive(Debug, PartialEq)]
+pub struct Error;
+
+#[derive(Debug, PartialEq)]
+pub struct OtherError;
+
+impl From<OtherError> for Error {
+    fn from(_: OtherError) -> Error {
+        Error
+    }
+}
+
+#[throws]
+pub fn iterator() -> impl Iterator<Item = u8> {
+    vec![1, 2].into_iter()
+}
+
+#[throws]
+pub fn nested_in_generics() -> Vec<impl Debug> {
+    vec![1, 2]
+}
+
+#[throws]
+pub fn nested_in_tuple() -> (impl Debug, impl Debug) {
+    (1, "two")
+}
+
+#[throws]
+pub fn nested_in_impl() -> impl Iterator<Item = impl Debug> {
+    vec![1, 2].into_iter()
+}
+
+#[throws]
+pub fn with_lifetime<'a>(input: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+    if input.is_empty() {
+        throw!(OtherError);
+    }
+    input.split(',')
+}
+
+#[throws]
+pub fn closure() -> impl Fn(u8) -> u8 {
+    |x| x + 1
+}
+
+#[throws]
+pub fn early_return(early: bool) -> impl Debug {
+    if early {
+        return 1;
+    }
+    2
+}
+
+#[throws(impl Debug)]
+pub fn error_position(fail: bool) -> u8 {
+    if fail {
+        throw!(OtherError);
+    }
+    1
+}
+
+#[throws(impl Debug)]
+pub fn error_position_question_mark(result: Result<u8, OtherError>) -> u8 {
+    result? + 1
+}
+
+#[throws(impl Debug as Poll)]
+pub fn error_position_poll(poll: Poll<Result<u8, OtherError>>) -> u8 {
+    poll?
+}
+
+#[throws(impl Debug)]
+pub fn both_positions() -> impl Debug {
+    if false {
+        throw!(OtherError);
+    }
+    1
+}
+
+#[try_fn]
+pub fn try_fn_nested() -> Result<Vec<impl Debug>, Error> {
+    vec![1]
+}
+
+#[try_fn]
+pub fn try_fn_error_position() -> Result<u8, impl Debug> {
+    throw!(OtherError);
+}
+
+trait Rpitit {
+    #[throws]
+    fn items(&self) -> impl Iterator<Item = u8>;
+
+    #[throws]
+    fn count(&self) -> impl Debug {
+        self.items()?.count()
+    }
+
+    #[throws]
+    async fn load(&self) -> impl Debug;
+}
+
+impl Rpitit for () {
+    #[throws]
+    fn items(&self) -> impl Iterator<Item = u8> {
+        0..2
+    }
+
+    #[throws]
+    async fn load(&self) -> impl Debug {
+        throw!(OtherError);
+    }
+}
+
+#[throws]
+pub async fn async_fn() -> impl Debug {
+    1
+}
+
+fn debug<T: Debug, E: Debug>(result: Result<T, E>) -> String {
+    format!("{:?}", result)
+}
+
+#[test]
+fn test_ok_position() {
+    assert_eq!(vec![1, 2], iterator().unwrap().collect::<Vec<_>>());
+    assert_eq!("Ok([1, 2])", debug(nested_in_generics()));
+    assert_eq!("Ok((1, \"two\"))", debug(nested_in_tuple()));
+    assert_eq!(
+        "[1, 2]",
+        format!("{:?}", nested_in_impl().unwrap().collect::<Vec<_>>())
+    );
+    assert_eq!(
+        vec!["a", "b"],
+        with_lifetime("a,b").unwrap().collect::<Vec<_>>()
+    );
+    assert!(with_lifetime("").is_err());
+    assert_eq!(2, closure().unwrap()(1));
+    assert_eq!("Ok(1)", debug(early_return(true)));
+    assert_eq!("Ok(2)", debug(early_return(false)));
+    assert_eq!("Ok([1])", debug(try_fn_nested()));
+}
+
+#[test]
+fn test_error_position() {
+    assert_eq!("Ok(1)", debug(error_position(false)));
+    assert_eq!("Err(OtherError)", debug(error_position(true)));
+    assert_eq!("Ok(2)", debug(error_position_question_mark(Ok(1))));
+    assert_eq!(
+        "Err(OtherError)",
+        debug(error_position_question_mark(Err(OtherError)))
+    );
+    assert!(error_position_poll(Poll::Pending).is_pending());
+    assert_eq!(
+        "Ready(Ok(1))",
+        format!("{:?}", error_position_poll(Poll::Ready(Ok(1))))
+    );
+    assert_eq!("Ok(1)", debug(both_positions()));
+    assert_eq!("Err(OtherError)", debug(try_fn_error_position()));
+}
+
+#[test]
+fn test_traits() {
+    assert_eq!("Ok(2)", debug(().count()));
+    assert_eq!("Err(Error)", debug(block_on(().load())));
+    assert_eq!("Ok(1)", debug(block_on(async_fn())));
+}
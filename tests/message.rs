@@ -0,0 +1,87 @@
+use culpa::{bail, ensure, throw, throws, try_block};
+
+#[throws(&'static str)]
+fn static_message() {
+    throw!("static message");
+}
+
+#[throws(&'static str)]
+fn ensure_static(value: u8) {
+    ensure!(value > 0, "value must be positive");
+}
+
+#[throws(&'static str)]
+fn bail_static() {
+    bail!("bailed");
+}
+
+#[test]
+fn test_static_messages() {
+    assert_eq!(Err("static message"), static_message());
+    assert_eq!(Ok(()), ensure_static(1));
+    assert_eq!(Err("value must be positive"), ensure_static(0));
+    assert_eq!(Err("bailed"), bail_static());
+}
+
+#[test]
+fn test_ensure_in_try_block() {
+    let result: Result<u8, &str> = try_block!(&str => {
+        ensure!(false, "in block");
+        1
+    });
+    assert_eq!(Err("in block"), result);
+}
+
+#[cfg(feature = "alloc")]
+mod alloc {
+    use culpa::{bail, ensure, throw, throws, try_block};
+
+    type Error = Box<dyn std::error::Error>;
+
+    #[throws(String)]
+    fn formatted(header: &str) {
+        throw!("bad header {}", header);
+    }
+
+    #[throws]
+    fn boxed(header: &str) {
+        throw!("bad header {header}, {}", 1);
+    }
+
+    #[throws]
+    fn ensured(value: u8) -> u8 {
+        ensure!(value < 10, "value {} is too large", value);
+        value
+    }
+
+    #[throws(String)]
+    fn bailed(value: u8) {
+        bail!("bailed with {}", value);
+    }
+
+    #[throws(impl std::fmt::Debug)]
+    fn exact() {
+        throw!("exact {}", 1);
+    }
+
+    #[test]
+    fn test_formatted_messages() {
+        assert_eq!(Err("bad header Host".to_string()), formatted("Host"));
+        assert_eq!("bad header Host, 1", boxed("Host").unwrap_err().to_string());
+        assert_eq!(1, ensured(1).unwrap());
+        assert_eq!(
+            "value 10 is too large",
+            ensured(10).unwrap_err().to_string()
+        );
+        assert_eq!(Err("bailed with 2".to_string()), bailed(2));
+        assert_eq!("\"exact 1\"", format!("{:?}", exact().unwrap_err()));
+    }
+
+    #[test]
+    fn test_in_try_block() {
+        let result: Result<(), String> = try_block!(String => {
+            throw!("in block {}", 1);
+        });
+        assert_eq!(Err("in block 1".to_string()), result);
+    }
+}
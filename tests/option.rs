@@ -17,3 +17,35 @@ fn test_outcome_true() {
 fn test_outcome_false() {
     assert_eq!(Some(0), foo(false))
 }
+
+#[throws(as Option)]
+fn throw_none(x: u8) -> u8 {
+    if x == 0 {
+        throw!(None);
+    } else if x == 1 {
+        throw!(culpa::NoneError);
+    }
+    x
+}
+
+#[test]
+fn test_throw_none() {
+    assert_eq!(None, throw_none(0));
+    assert_eq!(None, throw_none(1));
+    assert_eq!(Some(2), throw_none(2));
+}
+
+#[test]
+fn test_throw_empty_poll() {
+    use std::task::Poll;
+
+    fn poll_next(done: bool) -> Poll<Option<u8>> {
+        if done {
+            throw!();
+        }
+        Poll::Ready(Some(0))
+    }
+
+    assert_eq!(Poll::Ready(None), poll_next(true));
+    assert_eq!(Poll::Ready(Some(0)), poll_next(false));
+}
@@ -0,0 +1,97 @@
+use std::num::ParseIntError;
+use std::task::Poll;
+
+use culpa::{throw, throws, throws_closure, Fallible};
+
+#[derive(Debug, PartialEq, Fallible)]
+pub enum Reply<'a, T, C, E> {
+    #[culpa(ok)]
+    Done(T),
+    #[culpa(err)]
+    Failed(E),
+    #[allow(dead_code)]
+    Pending(&'a str, C),
+}
+
+#[derive(Debug, PartialEq, Fallible)]
+pub enum Limited<T, const N: usize> {
+    #[culpa(ok)]
+    Within(T),
+    #[culpa(err)]
+    Over(usize),
+}
+
+#[throws(&'static str as Reply<'static, @ok, u8, @err>)]
+fn reply(value: i32) -> i32 {
+    if value < 0 {
+        throw!("negative");
+    }
+    value * 2
+}
+
+#[throws(as Reply<'static, @ok, u8, &'static str>)]
+fn reply_without_error(value: i32) {
+    if value < 0 {
+        throw!("negative");
+    }
+}
+
+#[throws(as Limited<@ok, 4>)]
+fn limited(input: &str) -> &str {
+    if input.len() > 4 {
+        throw!(input.len());
+    }
+    input
+}
+
+#[throws(ParseIntError as Poll<Option<Result<@ok, @err>>>)]
+fn poll_next(input: Option<&str>) -> u8 {
+    match input {
+        Some(input) => Poll::Ready(Some(input.parse()?)),
+        None => Poll::Ready(None),
+    }
+}
+
+#[throws(as Result<@ok, ParseIntError>)]
+fn parse(input: &str) -> u8 {
+    input.parse()?
+}
+
+#[test]
+fn test_ok_in_the_middle() {
+    assert_eq!(reply(2), Reply::Done(4));
+    assert_eq!(reply(-2), Reply::Failed("negative"));
+    assert_eq!(reply_without_error(2), Reply::Done(()));
+    assert_eq!(reply_without_error(-2), Reply::Failed("negative"));
+}
+
+#[test]
+fn test_const_generic() {
+    assert_eq!(limited("four"), Limited::Within("four"));
+    assert_eq!(limited("seven"), Limited::Over(5));
+}
+
+#[test]
+fn test_nested() {
+    assert_eq!(poll_next(Some("1")), Poll::Ready(Some(Ok(1))));
+    assert_eq!(poll_next(None), Poll::Ready(None));
+    assert!(matches!(poll_next(Some("a")), Poll::Ready(Some(Err(_)))));
+}
+
+#[test]
+fn test_error_in_wrapper() {
+    assert_eq!(parse("1"), Ok(1));
+    assert!(parse("a").is_err());
+}
+
+#[test]
+fn test_closure() {
+    let closure = throws_closure!(&'static str as Reply<'static, @ok, u8, @err> => |value: i32| {
+        if value < 0 {
+            throw!("negative");
+        }
+        value
+    });
+    assert_eq!(closure(1), Reply::Done(1));
+    assert_eq!(closure(-1), Reply::Failed("negative"));
+}
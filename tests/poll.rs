@@ -0,0 +1,130 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll, Waker};
+
+use culpa::{throw, throws};
+
+#[derive(Debug, PartialEq)]
+pub struct Error(&'static str);
+
+pub struct Countdown(u8);
+
+impl Future for Countdown {
+    type Output = Result<&'static str, Error>;
+
+    #[throws(Error as Poll)]
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> &'static str {
+        match self.0 {
+            0 => Poll::Ready("liftoff"),
+            1 => throw!(Error("aborted")),
+            _ => {
+                self.0 -= 2;
+                cx.waker().wake_by_ref();
+                return Poll::Pending;
+            }
+        }
+    }
+}
+
+pub struct Wrapped(Countdown);
+
+impl Future for Wrapped {
+    type Output = Result<usize, Error>;
+
+    #[throws(Error as std::task::Poll)]
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> usize {
+        let message = ready!(Pin::new(&mut self.0).poll(cx))?;
+        Poll::Ready(message.len())
+    }
+}
+
+pub struct Numbers {
+    next: u8,
+    end: u8,
+}
+
+impl Numbers {
+    #[throws(Error as PollStream)]
+    fn poll_next(&mut self, _cx: &mut Context<'_>) -> u8 {
+        if self.next == self.end {
+            throw!();
+        } else if self.next == 13 {
+            throw!(Error("unlucky"));
+        }
+        self.next += 1;
+        Poll::Ready(Some(self.next - 1))
+    }
+}
+
+#[throws(Error as Poll<Result>)]
+pub fn explicit_poll_result(x: Poll<Result<u8, Error>>) -> u8 {
+    x?
+}
+
+#[throws(Error as Poll<Option<Result>>)]
+pub fn explicit_poll_stream(x: Poll<Option<Result<u8, Error>>>) -> u8 {
+    x?
+}
+
+fn poll<F: Future + Unpin>(future: &mut F) -> Poll<F::Output> {
+    Pin::new(future).poll(&mut Context::from_waker(Waker::noop()))
+}
+
+#[test]
+fn test_future() {
+    let mut countdown = Countdown(4);
+    assert_eq!(Poll::Pending, poll(&mut countdown));
+    assert_eq!(Poll::Pending, poll(&mut countdown));
+    assert_eq!(Poll::Ready(Ok("liftoff")), poll(&mut countdown));
+
+    let mut countdown = Countdown(3);
+    assert_eq!(Poll::Pending, poll(&mut countdown));
+    assert_eq!(Poll::Ready(Err(Error("aborted"))), poll(&mut countdown));
+}
+
+#[test]
+fn test_future_with_ready() {
+    let mut wrapped = Wrapped(Countdown(2));
+    assert_eq!(Poll::Pending, poll(&mut wrapped));
+    assert_eq!(Poll::Ready(Ok(7)), poll(&mut wrapped));
+
+    let mut wrapped = Wrapped(Countdown(1));
+    assert_eq!(Poll::Ready(Err(Error("aborted"))), poll(&mut wrapped));
+}
+
+#[test]
+fn test_stream() {
+    let mut cx = Context::from_waker(Waker::noop());
+
+    let mut numbers = Numbers { next: 0, end: 2 };
+    assert_eq!(Poll::Ready(Some(Ok(0))), numbers.poll_next(&mut cx));
+    assert_eq!(Poll::Ready(Some(Ok(1))), numbers.poll_next(&mut cx));
+    assert_eq!(Poll::Ready(None), numbers.poll_next(&mut cx));
+
+    let mut numbers = Numbers { next: 13, end: 20 };
+    assert_eq!(
+        Poll::Ready(Some(Err(Error("unlucky")))),
+        numbers.poll_next(&mut cx)
+    );
+}
+
+#[test]
+fn test_question_mark() {
+    assert_eq!(Poll::Ready(Ok(1)), explicit_poll_result(Poll::Ready(Ok(1))));
+    assert_eq!(Poll::Pending, explicit_poll_result(Poll::Pending));
+    assert_eq!(
+        Poll::Ready(Err(Error("a"))),
+        explicit_poll_result(Poll::Ready(Err(Error("a"))))
+    );
+
+    assert_eq!(
+        Poll::Ready(Some(Ok(1))),
+        explicit_poll_stream(Poll::Ready(Some(Ok(1))))
+    );
+    assert_eq!(Poll::Ready(None), explicit_poll_stream(Poll::Ready(None)));
+    assert_eq!(Poll::Pending, explicit_poll_stream(Poll::Pending));
+    assert_eq!(
+        Poll::Ready(Some(Err(Error("a")))),
+        explicit_poll_stream(Poll::Ready(Some(Err(Error("a")))))
+    );
+}
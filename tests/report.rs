@@ -0,0 +1,92 @@
+#![cfg(feature = "std")]
+
+mod common;
+
+use std::fmt;
+use std::num::NonZeroU8;
+use std::process::{ExitCode, Termination};
+
+use culpa::{throw, throws, Report};
+
+use common::block_on;
+
+#[derive(Debug)]
+struct Error(Option<Box<Error>>);
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("failed")
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.0.as_ref().map(|error| &**error as _)
+    }
+}
+
+#[throws(Error as Report)]
+fn reported(fail: bool) {
+    if fail {
+        throw!(Error(Some(Box::new(Error(None)))));
+    }
+}
+
+#[culpa::main]
+fn succeeds() -> u8 {
+    if true {
+        return 1;
+    }
+    2
+}
+
+#[culpa::main]
+fn fails() -> u8 {
+    throw!(Error(None));
+}
+
+#[culpa::main(Box<dyn std::error::Error>, exit_code = 3)]
+fn boxed_error() {
+    "x".parse::<u8>()?;
+}
+
+#[culpa::main(_)]
+async fn async_main() -> u8 {
+    1
+}
+
+#[test]
+fn test_report() {
+    assert!(reported(false).into_result().is_ok());
+    assert!(reported(true).into_result().is_err());
+    assert_eq!(ExitCode::SUCCESS, reported(false).report());
+    assert_eq!(ExitCode::from(1), reported(true).report());
+}
+
+#[test]
+fn test_main() {
+    assert_eq!(1, succeeds().into_result().unwrap());
+    assert_eq!(1, fails().exit_code());
+    assert!(fails().into_result().is_err());
+    assert_eq!(3, boxed_error().exit_code());
+    assert_eq!(ExitCode::from(3), boxed_error().report());
+}
+
+#[test]
+fn test_with_exit_code() {
+    let report = Report::<(), Error>::with_exit_code(Err(Error(None)), NonZeroU8::new(4).unwrap());
+    assert_eq!(4, report.exit_code());
+    assert_eq!(ExitCode::from(4), report.report());
+    assert_eq!(1, Report::<(), Error>::new(Err(Error(None))).exit_code());
+}
+
+#[test]
+fn test_async_main() {
+    assert_eq!(1, block_on(async_main()).into_result().unwrap());
+}
+
+#[test]
+#[culpa::main]
+fn test_returning_report() {
+    reported(false).into_result()?;
+}
@@ -0,0 +1,104 @@
+use std::collections::HashMap;
+use std::num::ParseIntError;
+
+use culpa::{throw, throws, throws_closure, try_block};
+
+#[derive(Debug, PartialEq)]
+enum Error {
+    Parse(ParseIntError),
+    Negative(i64),
+}
+
+impl From<ParseIntError> for Error {
+    fn from(error: ParseIntError) -> Error {
+        Error::Parse(error)
+    }
+}
+
+// Looks up a setting, which may be missing or fail to parse
+#[throws(Error as ResultOption)]
+fn setting(settings: &HashMap<&str, &str>, key: &str) -> i64 {
+    let value = settings.get(key)?.parse()?;
+    if value < 0 {
+        throw!(Error::Negative(value));
+    }
+    value
+}
+
+#[throws(ParseIntError as ResultOption)]
+fn first_number(input: &str) -> u8 {
+    let first = input.split_whitespace().next();
+    if first == Some("-") {
+        throw!();
+    }
+    if first == Some("0") {
+        return 0;
+    }
+    first?.parse()?
+}
+
+#[throws(ParseIntError as ResultOption)]
+fn check(input: &str) {
+    if input.is_empty() {
+        throw!();
+    }
+    input.parse::<u8>()?;
+}
+
+fn settings() -> HashMap<&'static str, &'static str> {
+    let mut settings = HashMap::new();
+    settings.insert("width", "80");
+    settings.insert("height", "tall");
+    settings.insert("depth", "-1");
+    settings
+}
+
+#[test]
+fn test_some() {
+    assert_eq!(setting(&settings(), "width"), Ok(Some(80)));
+    assert_eq!(first_number("0 1"), Ok(Some(0)));
+    assert_eq!(first_number("12 1"), Ok(Some(12)));
+    assert_eq!(check("1"), Ok(Some(())));
+}
+
+#[test]
+fn test_none() {
+    assert_eq!(setting(&settings(), "color"), Ok(None));
+    assert_eq!(first_number(""), Ok(None));
+    assert_eq!(first_number("- 1"), Ok(None));
+    assert_eq!(check(""), Ok(None));
+}
+
+#[test]
+fn test_error() {
+    assert!(matches!(
+        setting(&settings(), "height"),
+        Err(Error::Parse(_))
+    ));
+    assert_eq!(setting(&settings(), "depth"), Err(Error::Negative(-1)));
+    assert!(first_number("a").is_err());
+    assert!(check("a").is_err());
+}
+
+#[test]
+fn test_closure() {
+    let parse = throws_closure!(ParseIntError as ResultOption => |input: Option<&str>| -> u8 {
+        input?.parse()?
+    });
+    assert_eq!(parse(Some("1")), Ok(Some(1)));
+    assert_eq!(parse(None), Ok(None));
+    assert!(parse(Some("a")).is_err());
+}
+
+#[test]
+fn test_try_block() {
+    let values = ["1", "a"];
+    let block = |index: usize| {
+        try_block!(ParseIntError as ResultOption => {
+            values.get(index)?.parse::<u8>()?
+        })
+    };
+    assert_eq!(block(0), Ok(Some(1)));
+    assert!(block(1).is_err());
+    assert_eq!(block(2), Ok(None));
+}
@@ -0,0 +1,83 @@
+use std::error::Error as _;
+use std::fmt;
+use std::num::ParseIntError;
+
+use culpa::{throw, throws};
+
+#[derive(Debug, PartialEq)]
+pub struct MissingError;
+
+impl fmt::Display for MissingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("missing")
+    }
+}
+
+impl std::error::Error for MissingError {}
+
+#[throws(ParseIntError | MissingError)]
+pub fn parse_first(input: &str) -> u8 {
+    match input.split(',').next() {
+        Some(first) if !first.is_empty() => first.parse()?,
+        _ => throw!(MissingError),
+    }
+}
+
+#[throws(std::io::Error | std::fmt::Error | MissingError)]
+fn three(which: u8) {
+    match which {
+        0 => throw!(std::io::Error::other("io")),
+        1 => throw!(std::fmt::Error),
+        2 => throw!(MissingError),
+        _ => {}
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct Plain;
+
+#[throws(Plain | MissingError)]
+fn not_display(fail: bool) -> u8 {
+    if fail {
+        throw!(Plain);
+    }
+    1
+}
+
+#[test]
+fn test_sum_error() {
+    assert_eq!(12, parse_first("12,3").unwrap());
+    assert!(matches!(
+        parse_first("x"),
+        Err(ParseFirstError::ParseIntError(_))
+    ));
+    assert!(matches!(
+        parse_first(""),
+        Err(ParseFirstError::MissingError(MissingError))
+    ));
+}
+
+#[test]
+fn test_forwarding() {
+    let error = parse_first("").unwrap_err();
+    assert_eq!("missing", error.to_string());
+    assert_eq!("MissingError", format!("{:?}", error));
+    assert!(error.source().is_none());
+
+    let error: Box<dyn std::error::Error> = Box::new(parse_first("x").unwrap_err());
+    assert_eq!("invalid digit found in string", error.to_string());
+}
+
+#[test]
+fn test_module_variant_names() {
+    assert!(matches!(three(0), Err(ThreeError::Io(_))));
+    assert!(matches!(three(1), Err(ThreeError::Fmt(std::fmt::Error))));
+    assert!(matches!(three(2), Err(ThreeError::MissingError(_))));
+    assert!(three(3).is_ok());
+}
+
+#[test]
+fn test_not_display() {
+    assert_eq!(Some(1), not_display(false).ok());
+    assert_eq!("Plain", format!("{:?}", not_display(true).unwrap_err()));
+}
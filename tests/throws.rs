@@ -1,5 +1,9 @@
+mod common;
+
 use culpa::{throw, throws};
 
+use common::block_on;
+
 type Error = isize;
 
 #[throws(_)]
@@ -149,3 +153,68 @@ trait Example {
     #[throws]
     fn foo() -> i32;
 }
+
+#[allow(dead_code)]
+pub struct Data(u8);
+
+#[allow(dead_code, async_fn_in_trait)]
+pub trait Load {
+    #[throws(i32)]
+    async fn load(&self) -> Data;
+
+    #[throws(i32)]
+    fn load_send(&self) -> impl std::future::Future<Output = Data> + Send;
+
+    // Not a method, so the future itself is what is returned or thrown
+    #[throws(i32)]
+    fn make() -> impl std::future::Future<Output = Data> + Send;
+
+    #[throws(i32)]
+    fn load_default(&self) -> impl std::future::Future<Output = Data> + Send {
+        async move {
+            if false {
+                throw!(1);
+            }
+            Data(0)
+        }
+    }
+}
+
+pub struct Loader(u8);
+
+impl Load for Loader {
+    #[throws(i32)]
+    async fn load(&self) -> Data {
+        if self.0 == 0 {
+            throw!(0);
+        }
+        Data(self.0)
+    }
+
+    #[throws(i32)]
+    fn load_send(&self) -> impl std::future::Future<Output = Data> + Send {
+        let value = self.0;
+        async move {
+            if value == 0 {
+                return Data(1);
+            }
+            Data(value.checked_add(1).ok_or(2)?)
+        }
+    }
+
+    #[throws(i32)]
+    fn make() -> impl std::future::Future<Output = Data> + Send {
+        async move { Data(3) }
+    }
+}
+
+#[test]
+fn test_async_trait_methods() {
+    assert!(matches!(block_on(Loader(1).load()), Ok(Data(1))));
+    assert!(matches!(block_on(Loader(0).load()), Err(0)));
+    assert!(matches!(block_on(Loader(0).load_send()), Ok(Data(1))));
+    assert!(matches!(block_on(Loader(1).load_send()), Ok(Data(2))));
+    assert!(matches!(block_on(Loader(255).load_send()), Err(2)));
+    assert!(matches!(block_on(Loader(1).load_default()), Ok(Data(0))));
+    assert!(matches!(block_on(Loader::make().unwrap()), Data(3)));
+}
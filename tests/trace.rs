@@ -0,0 +1,197 @@
+#![cfg(feature = "trace")]
+
+use culpa::{throw, throws, Traced};
+
+#[derive(Debug, PartialEq)]
+pub struct Error;
+
+const INNER_LINE: u32 = line!() + 4;
+#[throws(Traced<Error>)]
+fn inner(fail: bool) -> u8 {
+    if fail {
+        throw!(Error);
+    }
+    1
+}
+
+const MIDDLE_LINE: u32 = line!() + 3;
+#[throws(Traced<Error>)]
+fn middle(fail: bool) -> u8 {
+    inner(fail)? + 1
+}
+
+const OUTER_LINE: u32 = line!() + 3;
+#[throws(Traced<Error>)]
+fn outer(fail: bool) -> u8 {
+    let value = middle(fail)?;
+    value + 1
+}
+
+const CONVERTED_LINE: u32 = line!() + 3;
+#[throws(Traced<Error>)]
+fn converted() {
+    Err(Error)?;
+}
+
+#[throws(Error)]
+fn untraced() {
+    throw!(Error);
+}
+
+const PROPAGATES_UNTRACED_LINE: u32 = line!() + 3;
+#[throws(Traced<Error>)]
+fn propagates_untraced() {
+    untraced()?;
+}
+
+fn lines(error: &Traced<Error>) -> Vec<u32> {
+    error.trace().map(|location| location.line()).collect()
+}
+
+#[test]
+fn test_trace() {
+    assert_eq!(3, outer(false).unwrap());
+    let error = outer(true).unwrap_err();
+    assert_eq!(&Error, error.error());
+    assert_eq!(vec![INNER_LINE, MIDDLE_LINE, OUTER_LINE], lines(&error));
+    assert!(error
+        .trace()
+        .all(|location| location.file().ends_with("trace.rs")));
+    assert_eq!(0, error.truncated());
+}
+
+#[test]
+fn test_question_mark_conversion() {
+    assert_eq!(vec![CONVERTED_LINE], lines(&converted().unwrap_err()));
+    assert_eq!(
+        vec![PROPAGATES_UNTRACED_LINE],
+        lines(&propagates_untraced().unwrap_err())
+    );
+}
+
+#[test]
+fn test_truncated() {
+    let first = std::panic::Location::caller();
+    let second = Traced::new(()).trace().next().unwrap();
+    let mut error = Traced::new(Error);
+    error.push(first);
+    error.push(first);
+    assert_eq!(2, error.trace().count());
+    for i in 0..Traced::<Error>::CAPACITY - 1 {
+        error.push(if i % 2 == 0 { second } else { first });
+    }
+    assert_eq!(Traced::<Error>::CAPACITY, error.trace().count());
+    assert_eq!(1, error.truncated());
+}
+
+#[test]
+fn test_debug() {
+    let error = Traced::new(Error);
+    let line = line!() - 1;
+    assert_eq!(
+        format!(
+            "Traced {{ error: Error, trace: [{}:{}:17] }}",
+            file!(),
+            line
+        ),
+        format!("{:?}", error)
+    );
+}
+
+#[test]
+fn test_other_errors_untouched() {
+    #[throws(Error)]
+    fn plain() {
+        untraced()?;
+    }
+    assert_eq!(Err(Error), plain());
+
+    #[throws(as Option)]
+    fn option() -> u8 {
+        None::<u8>?
+    }
+    assert_eq!(None, option());
+}
+
+const IMPL_TRAIT_LINE: u32 = line!() + 3;
+#[throws(Traced<Error>)]
+fn impl_trait(fail: bool) -> impl Iterator<Item = u8> {
+    inner(fail)?;
+    0..1
+}
+
+const POLL_LINE: u32 = line!() + 3;
+#[throws(Traced<Error> as std::task::Poll)]
+fn poll(fail: bool) -> u8 {
+    std::task::Poll::Ready(inner(fail)?)
+}
+
+#[test]
+fn test_inferred_types() {
+    assert_eq!(
+        vec![INNER_LINE, IMPL_TRAIT_LINE],
+        lines(&impl_trait(true).err().unwrap())
+    );
+    match poll(true) {
+        std::task::Poll::Ready(Err(error)) => {
+            assert_eq!(vec![INNER_LINE, POLL_LINE], lines(&error))
+        }
+        _ => panic!("expected an error"),
+    }
+
+    let closure_line = line!() + 1;
+    let closure = culpa::throws_closure!(Traced<Error> => || inner(true)?);
+    assert_eq!(
+        vec![INNER_LINE, closure_line],
+        lines(&closure().unwrap_err())
+    );
+}
+
+const BREAK_INNER_LINE: u32 = line!() + 4;
+#[throws(Traced<Error> as std::ops::ControlFlow)]
+fn break_inner(fail: bool) -> u8 {
+    if fail {
+        throw!(Error);
+    }
+    1
+}
+
+const BREAK_OUTER_LINE: u32 = line!() + 3;
+#[throws(Traced<Error> as std::ops::ControlFlow)]
+fn break_outer(fail: bool) -> u8 {
+    break_inner(fail)? + 1
+}
+
+#[test]
+fn test_control_flow() {
+    assert_eq!(2, break_outer(false).continue_value().unwrap());
+    let error = break_outer(true).break_value().unwrap();
+    assert_eq!(vec![BREAK_INNER_LINE, BREAK_OUTER_LINE], lines(&error));
+}
+
+const LOOKUP_LINE: u32 = line!() + 3;
+#[throws(Traced<Error> as ResultOption)]
+fn lookup(fail: Option<bool>) -> u8 {
+    inner(fail?)? + 1
+}
+
+const LOOKUP_UNTRACED_LINE: u32 = line!() + 4;
+#[throws(Traced<Error> as ResultOption)]
+fn lookup_untraced(fail: bool) -> u8 {
+    let result = if fail { Err(Error) } else { Ok(1) };
+    result?
+}
+
+#[test]
+fn test_result_option() {
+    assert_eq!(Some(2), lookup(Some(false)).unwrap());
+    assert_eq!(None, lookup(None).unwrap());
+    assert_eq!(
+        vec![INNER_LINE, LOOKUP_LINE],
+        lines(&lookup(Some(true)).unwrap_err())
+    );
+    assert_eq!(
+        vec![LOOKUP_UNTRACED_LINE],
+        lines(&lookup_untraced(true).unwrap_err())
+    );
+}
@@ -0,0 +1,190 @@
+use culpa::{throw, try_block};
+
+#[derive(Debug, PartialEq)]
+pub struct Error;
+
+#[derive(Debug, PartialEq)]
+pub struct OtherError;
+
+impl From<OtherError> for Error {
+    fn from(_: OtherError) -> Error {
+        Error
+    }
+}
+
+fn fallible(fail: bool) -> Result<u8, OtherError> {
+    match fail {
+        true => Err(OtherError),
+        false => Ok(1),
+    }
+}
+
+#[test]
+fn test_ok() {
+    let result = try_block!(Error => { fallible(false)? + 1 });
+    assert_eq!(Ok(2), result);
+}
+
+#[test]
+fn test_question_mark_exits_block() {
+    let mut reached = false;
+    let result = try_block!(Error => {
+        fallible(true)?;
+        reached = true;
+    });
+    assert_eq!(Err(Error), result);
+    assert!(!reached);
+}
+
+#[test]
+fn test_throw_exits_block() {
+    let result: Result<u8, Error> = try_block!(Error => {
+        if true {
+            throw!(OtherError);
+        }
+        1
+    });
+    assert_eq!(Err(Error), result);
+}
+
+#[test]
+fn test_default_error() {
+    let result = try_block!({ fallible(true)? });
+    assert_eq!(Err(Error), result);
+}
+
+#[test]
+fn test_option() {
+    let values = [1, 2];
+    assert_eq!(
+        Some(3),
+        try_block!(Option => { values.first()? + values.last()? })
+    );
+    assert_eq!(None, try_block!(Option => { values.get(2)? + 1 }));
+    assert_eq!(None::<u8>, try_block!(Option => { throw!() }));
+    assert_eq!(Some(1), try_block!(as Option => { 1 }));
+}
+
+#[test]
+fn test_question_mark_in_macros() {
+    fn in_vec(fail: bool) -> Result<Result<Vec<u8>, Error>, OtherError> {
+        Ok(try_block!(Error => { vec![1, fallible(fail)?] }))
+    }
+    fn in_format(fail: bool) -> Result<Result<String, Error>, OtherError> {
+        Ok(try_block!(Error => { format!("{}-{last}", 0, last = fallible(fail)?) }))
+    }
+    assert_eq!(Ok(Ok(vec![1, 1])), in_vec(false));
+    assert_eq!(Ok(Err(Error)), in_vec(true));
+    assert_eq!(Ok(Ok("0-1".to_string())), in_format(false));
+    assert_eq!(Ok(Err(Error)), in_format(true));
+
+    let result = try_block!(Error => { vec![fallible(true)?; 2] });
+    assert_eq!(Err(Error), result);
+    let result = try_block!(Error => { vec![format!("{}", fallible(true)?)] });
+    assert_eq!(Err(Error), result);
+}
+
+#[test]
+fn test_return_leaves_function() {
+    fn first_even(values: &[u8]) -> u8 {
+        for value in values {
+            let _ = try_block!(Error => {
+                if value % 2 == 0 {
+                    return *value;
+                }
+                fallible(true)?;
+            });
+        }
+        0
+    }
+    assert_eq!(4, first_even(&[1, 3, 4, 5]));
+    assert_eq!(0, first_even(&[1, 3]));
+}
+
+#[culpa::throws]
+fn first_even(values: &[u8]) -> u8 {
+    for value in values {
+        let _ = try_block!(Error => {
+            let _ = try_block!(Error => {
+                if *value == 0 {
+                    return 0;
+                }
+            });
+            if value % 2 == 0 {
+                return *value;
+            }
+            let closure = || -> Result<u8, Error> { Ok(*value) };
+            closure()?;
+        });
+    }
+    throw!(Error)
+}
+
+#[test]
+fn test_return_in_throwing_function() {
+    assert_eq!(Ok(4), first_even(&[1, 3, 4, 5]));
+    assert_eq!(Ok(0), first_even(&[1, 0]));
+    assert_eq!(Err(Error), first_even(&[1, 3]));
+}
+
+#[test]
+fn test_loops_in_block() {
+    let result = try_block!(Error => {
+        let mut total = 0;
+        for fail in [false, false, true] {
+            if total == 2 {
+                break;
+            }
+            total += fallible(fail)?;
+        }
+        total
+    });
+    assert_eq!(Ok(2), result);
+}
+
+#[test]
+fn test_labeled_loop_around_block() {
+    let mut total = 0;
+    'outer: for fail in [false, true, false] {
+        let _ = try_block!(Error => {
+            if fail {
+                break 'outer;
+            }
+            total += fallible(fail)?;
+        });
+    }
+    assert_eq!(1, total);
+}
+
+#[test]
+fn test_nested() {
+    let result = try_block!(Error => {
+        let inner = try_block!(OtherError => { fallible(true)? });
+        assert_eq!(Err(OtherError), inner);
+        inner? + 1
+    });
+    assert_eq!(Err(Error), result);
+}
+
+#[test]
+fn test_closure_in_block() {
+    let result = try_block!(Error => {
+        let f = || fallible(true);
+        f().is_err()
+    });
+    assert_eq!(Ok(true), result);
+}
+
+#[culpa::throws]
+fn in_throwing_function() -> u8 {
+    let inner = try_block!(Option => { None::<u8>? });
+    if inner.is_some() {
+        throw!(Error);
+    }
+    fallible(false)?
+}
+
+#[test]
+fn test_in_throwing_function() {
+    assert_eq!(Ok(1), in_throwing_function());
+}
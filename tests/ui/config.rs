@@ -0,0 +1,10 @@
+#[culpa::config]
+mod empty {}
+
+#[culpa::config(error = std::io::Error, exit_code = 2)]
+mod unknown {}
+
+#[culpa::config(error = std::io::Error)]
+fn not_a_module() {}
+
+fn main() {}
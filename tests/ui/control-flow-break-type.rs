@@ -0,0 +1,4 @@
+#[culpa::throws(as ControlFlow)]
+fn visit() {}
+
+fn main() {}
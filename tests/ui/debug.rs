@@ -0,0 +1,19 @@
+#[culpa::throws(i32, debug)]
+fn answer() -> u8 {
+    42
+}
+
+#[culpa::try_fn(debug)]
+fn length(input: &str) -> Result<usize, i32> {
+    input.len()
+}
+
+#[culpa::throws(i32)]
+fn closure() {
+    let _ = #[culpa::throws(i32, debug)]
+    || 1;
+}
+
+fn main() {
+    let _ = culpa::throws_closure!(i32, debug => |value: u8| value);
+}
@@ -0,0 +1,53 @@
+#![deny(deprecated)]
+
+use culpa::{throws, try_fn};
+
+#[derive(Debug)]
+pub struct Error;
+
+#[throws]
+pub fn ok_tail() -> u8 {
+    Ok(1)
+}
+
+#[throws]
+pub fn err_in_branch(fail: bool) -> u8 {
+    if fail {
+        Err(Error)
+    } else {
+        1
+    }
+}
+
+#[throws(as Option)]
+pub fn some_return(value: u8) -> u8 {
+    match value {
+        0 => return Some(0),
+        value => value,
+    }
+}
+
+#[try_fn]
+pub fn try_fn_ok_tail() -> Result<u8, Error> {
+    Ok(1)
+}
+
+// None of these are warned about, since the ok type could be the wrapper
+
+#[throws]
+pub fn result_ok_type() -> Result<u8, Error> {
+    Ok(1)
+}
+
+#[try_fn]
+pub fn option_ok_type() -> Result<Option<u8>, Error> {
+    Some(1)
+}
+
+#[throws]
+pub fn inferred_closure() {
+    let closure = #[throws] || Ok::<u8, Error>(1);
+    closure()?;
+}
+
+fn main() {}
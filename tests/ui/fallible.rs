@@ -0,0 +1,37 @@
+use culpa::Fallible;
+
+#[derive(Fallible)]
+struct NotAnEnum;
+
+#[derive(Fallible)]
+enum NoVariants {
+    A,
+}
+
+#[derive(Fallible)]
+enum Duplicate {
+    #[culpa(ok)]
+    A,
+    #[culpa(ok)]
+    B,
+}
+
+#[derive(Fallible)]
+enum Unknown {
+    #[culpa(maybe)]
+    A,
+}
+
+#[derive(Fallible)]
+enum TooManyFields {
+    #[culpa(ok)]
+    A(u8, u8),
+}
+
+#[derive(Fallible)]
+enum EmptyWithField {
+    #[culpa(empty)]
+    A(u8),
+}
+
+fn main() {}
@@ -0,0 +1,20 @@
+use std::future::{ready, Future};
+
+use culpa::throws;
+
+struct Store;
+
+impl Store {
+    #[throws(i32)]
+    fn load(&self) -> impl Future<Output = u8> {
+        ready(0)
+    }
+
+    #[throws(i32)]
+    fn save(&self) -> impl Future<Output = ()> {
+        #[throws(i32)]
+        async {}
+    }
+}
+
+fn main() {}
@@ -0,0 +1,17 @@
+use culpa::{throw, throws};
+
+#[derive(Debug)]
+struct NotFound;
+
+#[derive(Debug)]
+struct Denied;
+
+#[throws(impl std::fmt::Debug)]
+fn open(exists: bool) {
+    if !exists {
+        throw!(NotFound);
+    }
+    throw!(Denied);
+}
+
+fn main() {}
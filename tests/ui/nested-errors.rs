@@ -0,0 +1,11 @@
+#[culpa::throws(i32)]
+fn closures() {
+    let _ = #[culpa::throws(i32 as &Result)]
+    || {};
+    let _ = #[culpa::throws(i32 as Result<>)]
+    || {};
+}
+
+fn main() {
+    let _ = culpa::throws_closure!(i32 as &Result => || {});
+}
@@ -0,0 +1,7 @@
+#[culpa::throws(i32)]
+struct Foo;
+
+#[culpa::try_fn]
+const BAR: i32 = 0;
+
+fn main() {}
@@ -0,0 +1,11 @@
+use culpa::{nothrow, throws};
+
+#[nothrow]
+fn outside() {}
+
+#[throws(i32)]
+static NOT_A_FUNCTION: i32 = 0;
+
+fn main() {
+    outside();
+}
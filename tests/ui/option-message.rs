@@ -0,0 +1,13 @@
+use culpa::{throw, throws};
+
+#[throws(as Option)]
+fn first(values: &[u8]) -> u8 {
+    if values.is_empty() {
+        throw!("no values");
+    }
+    values[0]
+}
+
+fn main() {
+    let _ = first(&[1]);
+}
@@ -0,0 +1,27 @@
+use culpa::{throws, try_block};
+
+#[throws(anyhow::Error)]
+fn parse(input: &str) -> u8 {
+    let value = try_block!(anyhow::Error => {
+        anyhow::ensure!(!input.is_empty(), "empty input");
+        input.parse::<u8>()?
+    })?;
+    if value == 0 {
+        anyhow::bail!("zero");
+    }
+    value
+}
+
+#[throws(impl std::fmt::Debug)]
+fn check(value: u8) {
+    if value == 0 {
+        anyhow::bail!("zero");
+    }
+}
+
+fn main() {
+    assert_eq!(1, parse("1").unwrap());
+    assert!(parse("").is_err());
+    assert!(parse("0").is_err());
+    assert!(check(0).is_err());
+}
@@ -0,0 +1,6 @@
+#[culpa::throws(as ResultOption)]
+fn lookup() -> u8 {
+    1
+}
+
+fn main() {}
@@ -0,0 +1,7 @@
+#[culpa::throws(i32, Option)]
+fn two_errors() {}
+
+#[culpa::throws(i32 as Result, u8)]
+fn trailing() {}
+
+fn main() {}
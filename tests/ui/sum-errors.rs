@@ -0,0 +1,45 @@
+use culpa::throws;
+
+struct NotFound;
+struct Denied;
+
+mod io {
+    pub struct NotFound;
+}
+
+#[throws(NotFound | io::NotFound)]
+fn same_name() {}
+
+#[throws(NotFound | &'static str)]
+fn not_a_path() {}
+
+struct Wrapped<T>(T);
+struct Borrowed<'a>(&'a str);
+
+#[throws(NotFound | Wrapped<T>)]
+fn generic<T>(value: T) {}
+
+#[throws(Borrowed<'a> | NotFound)]
+fn borrowed<'a>(value: &'a str) {}
+
+struct Store;
+
+impl Store {
+    #[throws(NotFound | Denied)]
+    fn method(&self) {}
+}
+
+#[throws(NotFound | Denied)]
+impl Store {
+    fn block(&self) {}
+
+    fn other_block(&self) {}
+}
+
+#[throws]
+fn closure() {
+    let _ = #[throws(NotFound | Denied)]
+    || {};
+}
+
+fn main() {}
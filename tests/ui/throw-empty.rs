@@ -0,0 +1,16 @@
+use culpa::{throw, throws};
+
+#[throws(i32)]
+fn result() {
+    throw!();
+}
+
+#[derive(Default)]
+struct Error;
+
+#[throws(Error)]
+fn result_with_default_error() {
+    throw!();
+}
+
+fn main() {}
@@ -0,0 +1,18 @@
+use culpa::try_block;
+
+fn main() {
+    for input in ["1", "a"] {
+        let _ = try_block!(std::num::ParseIntError => {
+            if input.is_empty() {
+                continue;
+            }
+            let value = input.parse::<u8>()?;
+            if value == 1 {
+                break;
+            }
+            for _ in 0..value {
+                break;
+            }
+        });
+    }
+}
@@ -0,0 +1,18 @@
+use culpa::try_block;
+
+macro_rules! first {
+    ($first:expr, $($rest:tt)*) => {
+        $first
+    };
+}
+
+fn parse(input: &str) -> Result<u8, std::num::ParseIntError> {
+    let parsed = try_block!(std::num::ParseIntError => {
+        first!(input.parse::<u8>()?, 1)
+    });
+    parsed
+}
+
+fn main() {
+    let _ = parse("1");
+}
@@ -0,0 +1,10 @@
+#[culpa::try_fn(i32)]
+fn arguments() -> Result<(), i32> {}
+
+#[culpa::throws(i32)]
+fn closure_arguments() {
+    let _ = #[culpa::try_fn(i32)]
+    || -> Result<(), i32> {};
+}
+
+fn main() {}
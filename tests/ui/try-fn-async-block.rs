@@ -0,0 +1,7 @@
+#[culpa::throws(i32)]
+fn async_block() {
+    let _ = #[culpa::try_fn]
+    async {};
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+struct MyTryType<'a, T>(&'a T);
+
+#[culpa::throws(as MyTryType<'static>)]
+fn lifetime() {}
+
+#[culpa::throws(i32 as Result<>)]
+fn empty() {}
+
+fn main() {}
@@ -0,0 +1,7 @@
+#[culpa::throws(i32 as &Result)]
+fn reference() {}
+
+#[culpa::throws(i32 as <Result as Clone>::Output)]
+fn qualified() {}
+
+fn main() {}
@@ -0,0 +1,13 @@
+#[culpa::throws(as Result<u8, @err>)]
+fn missing_ok() {}
+
+#[culpa::throws(as Result<@ok, @err>)]
+fn missing_error() {}
+
+#[culpa::throws(i32 as Result<@ok, i32>)]
+fn missing_err() {}
+
+#[culpa::throws(i32 as Result<@ok, @error>)]
+fn unknown() {}
+
+fn main() {}